@@ -8,59 +8,256 @@ use crate::types::{mangle_types, type_mangle, AtomType};
 use crate::err;
 use crate::err::{ATErr, ErrKind};
 
-use crate::parser::ast::{Blueprint, Expr, Ident, Node};
+use crate::parser::ast::{
+    untyped, AtomDef, Blueprint, Expr, Ident, Literal, ModDef, Node, Pattern,
+};
 
 use super::*;
 
 impl Analyzer {
     #[inline]
-    pub fn analyz_body(&mut self, body: Vec<Node>, top: bool) -> Result<Vec<Node>, ErrKind> {
+    pub fn analyz_body(&mut self, body: Vec<Node>, top: bool) -> Vec<Node> {
         let mut analyzed_body = vec![];
         if !top {
             self.env.child();
         }
 
         for node in body {
-            if let &Expr::Use(ref path) = &node.expr {
-                let abs = format!("{}/{}", self.workdir.clone(), path);
-                let read = fs::read_to_string(abs)
-                    .expect(format!("failed to open path {} to use", path).as_str());
+            if let Expr::Use {
+                module,
+                file,
+                names,
+            } = node.expr
+            {
+                let mut used = self.analyz_use(module, file, names, node.span);
+                analyzed_body.append(&mut used);
+                continue;
+            }
+            analyzed_body.push(self.analyz(node));
+        }
+
+        if !top {
+            self.env.parent();
+        }
+        analyzed_body
+    }
 
-                use crate::parser::Parser;
-                let mut parser = Parser::new(read);
+    // brings names of a mod into scope, a used file is a mod named after the file
+    // the code at the top of a used file runs where it is used
+    pub fn analyz_use(
+        &mut self,
+        module: String,
+        file: bool,
+        names: Vec<Ident>,
+        span: Span,
+    ) -> Vec<Node> {
+        let mut body = Vec::new();
+
+        let path = if file {
+            match self.use_file(&module, span) {
+                Some((name, mut ast)) => {
+                    body.append(&mut ast);
+                    name
+                }
+                None => return body,
+            }
+        } else {
+            // a mod next to the current one comes first
+            let nested = format!("{}::{}", self.module, module);
+            if !self.module.is_empty() && self.mods.contains_key(&nested) {
+                nested
+            } else {
+                module
+            }
+        };
 
-                let ast = parser.parse_prog();
+        let members = match self.mods.get(&path) {
+            Some(members) => members.clone(),
+            None => {
+                self.report(ATErr::new(
+                    ErrKind::UndeclaredVar,
+                    format!("cannot find mod {}", path),
+                    span,
+                ));
+                return body;
+            }
+        };
 
-                self.blueprints(parser.functions)?;
-                let mut ast = self.analyz_body(ast, true)?;
+        let names = if names.is_empty() {
+            members
+                .into_iter()
+                .map(|name| Ident::UnTagged(name, span))
+                .collect()
+        } else {
+            names
+        };
+        for name in names {
+            self.use_name(&path, name);
+        }
+        body
+    }
 
-                analyzed_body.append(&mut ast);
-                continue;
+    // analyzes a used file once and gives back the mod it declares
+    // the code at its top is only given back the first time
+    fn use_file(&mut self, path: &str, span: Span) -> Option<(String, Vec<Node>)> {
+        // files are used relative to the file using them
+        let dir = match Path::new(&self.file).parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => PathBuf::from(&self.workdir),
+        };
+        let shown = dir.join(path).to_string_lossy().to_string();
+
+        let read = fs::canonicalize(&shown)
+            .and_then(|abs| fs::read_to_string(&abs).map(|code| (abs, code)));
+        let (abs, code) = match read {
+            Ok(read) => read,
+            Err(_) => {
+                self.report(ATErr::new(
+                    ErrKind::UndeclaredVar,
+                    format!("failed to open path {} to use", path),
+                    span,
+                ));
+                return None;
             }
-            analyzed_body.push(self.analyz(node)?);
+        };
+
+        if let Some(name) = self.used.get(&abs) {
+            return Some((name.clone(), Vec::new()));
         }
 
-        if !top {
-            self.env.parent();
+        if let Some(start) = self.using.iter().position(|(using, _)| using == &abs) {
+            let chain: Vec<&str> = self.using[start..]
+                .iter()
+                .map(|(_, shown)| shown.as_str())
+                .chain([shown.as_str()])
+                .collect();
+
+            self.report(
+                ATErr::new(ErrKind::UseCycle, format!("{} uses itself", path), span)
+                    .note(format!("use chain: {}", chain.join(" -> ")).as_str()),
+            );
+            return None;
+        }
+
+        let name = file_module(path);
+
+        use crate::parser::Parser;
+        let mut parser = Parser::new(code, shown.clone()).in_module(&name);
+
+        let ast = parser.parse_prog();
+        self.errors.append(&mut parser.errors());
+
+        // errors inside the used file point at it
+        let parent_file = std::mem::replace(&mut self.file, shown.clone());
+        let parent_module = std::mem::replace(&mut self.module, name.clone());
+        self.using.push((abs.clone(), shown));
+        self.mods(parser.mods);
+        self.atoms(parser.atoms);
+        self.blueprints(parser.functions);
+        let ast = self.analyz_body(ast, true);
+        self.using.pop();
+        self.file = parent_file;
+        self.module = parent_module;
+
+        self.used.insert(abs, name.clone());
+        Some((name, ast))
+    }
+
+    // declares name as another name for {path}::{name}
+    fn use_name(&mut self, path: &str, name: Ident) {
+        let full = format!("{}::{}", path, name.val());
+        let symbol = match self.env.get(&full) {
+            Some(symbol) => symbol.clone(),
+            None => {
+                self.report(ATErr::new(
+                    ErrKind::UndeclaredVar,
+                    format!("{} has no member named {}", path, name.val()),
+                    name.span(),
+                ));
+                return;
+            }
+        };
+
+        if let Some(declared) = self.env.get(name.val()) {
+            // already brought into scope by another use
+            if declared.ty == symbol.ty {
+                return;
+            }
+
+            let declared = declared.span;
+            self.report(
+                ATErr::new(
+                    ErrKind::VarAlreadyDeclared,
+                    format!("{} is already declared", name.val()),
+                    name.span(),
+                )
+                .label(declared, "declared here")
+                .note(format!("use {} to refer to it instead", full).as_str()),
+            );
+            return;
         }
-        Ok(analyzed_body)
+
+        self.env.add(Symbol {
+            name: name.val().clone(),
+            span: name.span(),
+            ..symbol
+        });
     }
 
     #[inline]
-    pub fn analyz_items(&mut self, items: Vec<Node>) -> Result<Vec<Node>, ErrKind> {
+    pub fn analyz_items(&mut self, items: Vec<Node>) -> Vec<Node> {
         let mut analyzed_items = vec![];
         for node in items {
-            analyzed_items.push(self.analyz(node)?);
+            analyzed_items.push(self.analyz_value(node));
         }
-        Ok(analyzed_items)
+        analyzed_items
+    }
+
+    // a value that is stored or passed on, what gives nothing cannot be one
+    fn analyz_value(&mut self, node: Node) -> Node {
+        let value = self.analyz(node);
+        if value.ty.kind != AtomKind::Basic(BasicType::Void) {
+            return value;
+        }
+
+        let err = match value.expr {
+            Expr::ForExpr { .. } | Expr::WhileExpr { .. } => ATErr::new(
+                ErrKind::InvaildType,
+                "a loop gives no value".to_string(),
+                value.span,
+            )
+            .note("push what it makes to a List and use the List"),
+            Expr::Block(_) => ATErr::new(
+                ErrKind::InvaildType,
+                "this block gives no value".to_string(),
+                value.span,
+            )
+            .note("a block gives the value it ends with"),
+            Expr::IfExpr { .. } | Expr::MatchExpr { .. } => ATErr::new(
+                ErrKind::InvaildType,
+                "no branch here gives a value".to_string(),
+                value.span,
+            )
+            .note("a branch gives the value it ends with"),
+            _ => ATErr::new(
+                ErrKind::InvaildType,
+                "this gives no value".to_string(),
+                value.span,
+            ),
+        };
+        self.report(err);
+        poison(value.span)
     }
 
     pub fn analyz_prog(
         exprs: Vec<Node>,
         functions: Vec<Blueprint>,
+        atoms: Vec<AtomDef>,
+        mods: Vec<ModDef>,
         workdir: String,
-    ) -> Result<Vec<Node>, ErrKind> {
-        let mut analyzer = Analyzer::new(workdir);
+        file: String,
+    ) -> Result<Vec<Node>, Vec<ATErr>> {
+        let mut analyzer = Analyzer::new(workdir, file);
         let mut analyzed_prog = Vec::new();
 
         analyzer.import(
@@ -77,12 +274,20 @@ impl Analyzer {
             }],
         );
 
+        analyzer.mods(mods);
+        // atoms are declared first so functions can use them as tags
+        analyzer.atoms(atoms);
         // setting our env blueprints to our uncompiled functions (blueprints are then compiled pased on call arguments)
-        analyzer.blueprints(functions)?;
-        analyzed_prog.append(&mut analyzer.analyz_body(exprs, true)?);
+        analyzer.blueprints(functions);
+        analyzed_prog.append(&mut analyzer.analyz_body(exprs, true));
+
+        if !analyzer.errors.is_empty() {
+            return Err(analyzer.errors);
+        }
 
         analyzed_prog = [
             analyzer.imports.clone(),
+            analyzer.atoms.clone(),
             analyzer.functions.clone(),
             analyzed_prog,
         ]
@@ -90,18 +295,39 @@ impl Analyzer {
         Ok(analyzed_prog)
     }
 
-    pub fn analyz(&mut self, node: Node) -> Result<Node, ErrKind> {
+    // analyzes a node, errors raised while analyzing it point at its span
+    // a node that fails to analyze is replaced with a poison node so we can keep going
+    pub fn analyz(&mut self, node: Node) -> Node {
+        self.analyz_as(node, true)
+    }
+
+    // an if or a block whose value is thrown away does not need to give one
+    fn analyz_as(&mut self, node: Node, used: bool) -> Node {
+        let parent_span = self.span;
+        self.span = node.span;
+
+        let analyzed = match self.analyz_node(node, used) {
+            Ok(node) => node,
+            Err(_) => poison(self.span),
+        };
+
+        self.span = parent_span;
+        analyzed
+    }
+
+    fn analyz_node(&mut self, node: Node, used: bool) -> Result<Node, ErrKind> {
         match node.expr.clone() {
             Expr::Literal(literal) => {
                 let ty = literal.get_ty();
                 Ok(Node {
                     expr: Expr::Literal(literal),
                     ty,
+                    span: self.span,
                 })
             }
 
             Expr::ListExpr(items) => {
-                let items = self.analyz_items(items)?;
+                let items = self.analyz_items(items);
 
                 let item_ty = if items.len() > 0 {
                     (&items.first().unwrap()).ty.clone()
@@ -117,8 +343,12 @@ impl Analyzer {
                         break;
                     }
 
+                    if item.ty.is_error() || item_ty.is_error() {
+                        continue;
+                    }
+
                     if &item.ty != &item_ty {
-                        err!(self, ErrKind::InvaildType, format!("list items have to be of the same type, item {} is of an invaild type", i-1));
+                        err!(self at item.span, ErrKind::InvaildType, format!("list items have to be of the same type, item {} is of an invaild type", i));
                     }
                 }
                 let ty = AtomType {
@@ -126,14 +356,19 @@ impl Analyzer {
                     details: None,
                 };
                 let expr = Expr::ListExpr(items);
-                Ok(Node { expr, ty })
+                Ok(Node {
+                    expr,
+                    ty,
+                    span: self.span,
+                })
             }
 
             Expr::BinaryExpr { op, left, right } => self.analyz_binary_expr(*left, *right, op),
+            Expr::UnaryExpr { op, operand } => self.analyz_unary_expr(op, *operand),
             Expr::Ident(id) => self.analyz_id(id),
 
-            Expr::VarDeclare { name, val } => self.analyz_var_declare(name, *val),
-            Expr::VarAssign { name, val } => self.analyz_var_assign(*name, *val),
+            Expr::VarDeclare { name, val, mutable } => self.analyz_var_declare(name, *val, mutable),
+            Expr::VarAssign { name, val, op } => self.analyz_var_assign(*name, *val, op),
 
             Expr::Discard(expr) => {
                 let ty = AtomType {
@@ -141,34 +376,32 @@ impl Analyzer {
                     details: None,
                 };
 
-                let expr = self.analyz(*expr)?;
+                let expr = self.analyz_as(*expr, false);
                 let expr = Expr::Discard(Box::new(expr));
 
-                Ok(Node { expr, ty })
-            }
-
-            Expr::PosInfo(x, line, column) => {
-                self.line = line;
-                self.column = column;
                 Ok(Node {
-                    expr: Expr::PosInfo(x, line, column),
-                    ty: AtomType {
-                        kind: AtomKind::Basic(BasicType::Void),
-                        details: None,
-                    },
+                    expr,
+                    ty,
+                    span: self.span,
                 })
             }
 
             Expr::RetExpr(expr) => {
-                let expr = self.analyz(*expr)?;
+                let expr = self.analyz(*expr);
                 let ty = expr.ty.clone();
 
                 let expr = Expr::RetExpr(Box::new(expr));
-                Ok(Node { expr, ty })
+                Ok(Node {
+                    expr,
+                    ty,
+                    span: self.span,
+                })
             }
 
             Expr::FnCall { name, args } => self.analyz_call(*name, args),
+            Expr::NewExpr { atom, args } => self.analyz_new(*atom, args),
             Expr::Extern { name, params } => self.analyz_extern(name, params),
+            Expr::Lambda { params, body, .. } => self.analyz_lambda(params, body),
 
             Expr::IfExpr {
                 condition,
@@ -181,33 +414,54 @@ impl Analyzer {
                     Some(alt) => Some(*alt),
                     None => None,
                 },
+                used,
             ),
 
             Expr::Block(block) => {
-                let block = self.analyz_body(block, false)?;
+                let block = self.analyz_value_body(block, used);
 
-                let last = block.last();
-                let ty = if last.is_none() {
+                let ty = if used {
+                    body_value(&block)
+                } else {
                     AtomType {
                         kind: AtomKind::Basic(BasicType::Void),
                         details: None,
                     }
-                } else {
-                    last.unwrap().ty.clone()
                 };
                 let expr = Expr::Block(block);
 
-                Ok(Node { expr, ty })
+                Ok(Node {
+                    expr,
+                    ty,
+                    span: self.span,
+                })
             }
 
-            Expr::WhileExpr { condition, body } => self.analyz_while_expr(*condition, body),
+            Expr::WhileExpr {
+                condition,
+                body,
+                label,
+            } => self.analyz_while_expr(*condition, body, label),
+            Expr::ForExpr {
+                var,
+                iter,
+                body,
+                label,
+            } => self.analyz_for_expr(var, *iter, body, label),
+            Expr::Break(ref label) | Expr::Continue(ref label) => self.analyz_jump(node, label),
+            Expr::MatchExpr { value, arms } => self.analyz_match_expr(*value, arms, used),
+            Expr::RangeExpr { start, end } => self.analyz_range(*start, *end),
 
             Expr::MemberExpr { parent, child } => self.analyz_member(*parent, child),
             Expr::IndexExpr { parent, index } => self.analyz_index(*parent, *index),
 
             Expr::SpecExpr { parent, spec } => {
-                let parent = Box::new(self.analyz(*parent)?);
-                let spec = self.analyz_items(spec)?;
+                let parent = Box::new(self.analyz(*parent));
+                let spec = self.analyz_items(spec);
+
+                if parent.ty.is_error() || spec.iter().any(|x| x.ty.is_error()) {
+                    return Ok(poison(self.span));
+                }
 
                 if !parent.ty.is_type() {
                     err!(
@@ -230,7 +484,14 @@ impl Analyzer {
                     );
                 }
 
-                let spec_types: Vec<AtomType> = spec.iter().map(|x| x.ty.clone()).collect();
+                // List(int) holds ints, not the type int
+                let spec_types: Vec<AtomType> = spec
+                    .iter()
+                    .map(|x| AtomType {
+                        kind: x.ty.kind.clone(),
+                        details: None,
+                    })
+                    .collect();
 
                 let ty = if let &AtomKind::Atom(ref atom) = &parent.ty.kind {
                     AtomType {
@@ -238,14 +499,82 @@ impl Analyzer {
                         details: Some(AtomDetails::Type),
                     }
                 } else {
-                    panic!("type {} is not an atom", parent.ty);
+                    err!(
+                        self,
+                        ErrKind::InvaildType,
+                        format!("type {} takes no generics", parent.ty)
+                    );
                 };
 
                 Ok(Node {
                     expr: Expr::SpecExpr { parent, spec },
                     ty,
+                    span: self.span,
+                })
+            }
+            Expr::FnType { params, ret } => {
+                let params: Vec<Node> =
+                    params.into_iter().map(|param| self.analyz(param)).collect();
+                let ret = ret.map(|ret| Box::new(self.analyz(*ret)));
+
+                let types = params.iter().chain(ret.as_deref());
+                if types.clone().any(|ty| ty.ty.is_error()) {
+                    return Ok(poison(self.span));
+                }
+                if let Some(value) = types.clone().find(|ty| !ty.ty.is_type()) {
+                    err!(
+                        self at value.span,
+                        ErrKind::InvaildType,
+                        format!("{} is not a type", value.ty)
+                    );
+                }
+
+                let func = FunctionType {
+                    params: params
+                        .iter()
+                        .map(|param| AtomType {
+                            kind: param.ty.kind.clone(),
+                            details: None,
+                        })
+                        .collect(),
+                    return_type: Box::new(AtomType {
+                        kind: match &ret {
+                            Some(ret) => ret.ty.kind.clone(),
+                            None => AtomKind::Basic(BasicType::Void),
+                        },
+                        details: None,
+                    }),
+                };
+                Ok(Node {
+                    expr: Expr::FnType { params, ret },
+                    ty: AtomType {
+                        kind: AtomKind::Function(func),
+                        details: Some(AtomDetails::Type),
+                    },
+                    span: self.span,
                 })
             }
+            // built by the parser already typed with what it converts into
+            Expr::As(inner) => {
+                let inner = self.analyz(*inner);
+                if inner.ty.is_error() {
+                    return Ok(poison(self.span));
+                }
+                if inner.ty == node.ty {
+                    return Ok(inner);
+                }
+                if !can_implicitly_convert(&inner.ty.kind, &node.ty.kind) {
+                    err!(
+                        self,
+                        ErrKind::InvaildType,
+                        format!("cannot convert {} into {}", inner.ty, node.ty)
+                    );
+                }
+                Ok(ty_as(&node.ty, inner))
+            }
+
+            // the parser already reported why this node failed
+            Expr::Error => Ok(poison(self.span)),
             _ => todo!("node {:#?}", node),
         }
     }
@@ -279,11 +608,17 @@ impl Analyzer {
             ty: ty.clone(),
             value: None,
             expected: None,
+            mutable: false,
+            span: name.span(),
         });
 
         let expr = Expr::Extern { name, params };
 
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
     pub fn analyz_blueprint(
@@ -298,8 +633,27 @@ impl Analyzer {
             }
         }
 
+        let parent_span = self.span;
+        self.span = blueprint.span;
+        let parent_file = std::mem::replace(&mut self.file, blueprint.file.clone());
+        let parent_module = std::mem::replace(&mut self.module, module_of(blueprint.name.val()));
+        // the new method of an atom is the only place its parts can be set
+        let constructing = blueprint
+            .name
+            .val()
+            .split('$')
+            .next()
+            .and_then(|name| name.strip_suffix("dotnew"))
+            .map(|atom| atom.to_string());
+        let parent_constructing = std::mem::replace(&mut self.constructing, constructing);
+        // a break in a function cannot leave a loop around its call
+        let parent_loops = std::mem::take(&mut self.loops);
+        // nor can it use the variables of a lambda calling it
+        let parent_lambdas = std::mem::take(&mut self.lambdas);
+
         self.env.child();
-        self.expect_as(&mangle, &blueprint.name)?;
+        // an invaild return tag is already reported, the function is then left without an expected type
+        let _ = self.expect_as(&mangle, &blueprint.name);
         // allows for the function to call itself
         let placeholder = FunctionType {
             return_type: Box::new(AtomType {
@@ -319,25 +673,29 @@ impl Analyzer {
 
                 value: None,
                 expected: None,
+                mutable: true,
+                span: arg.span(),
             });
 
-            typed_params.push(Ident::Typed(types[i].clone(), arg.val().clone()))
+            typed_params.push(Ident::Typed(
+                Box::new(types[i].clone()),
+                arg.val().clone(),
+                arg.span(),
+            ))
         }
 
-        let body = self.analyz_body(blueprint.body, false)?;
+        let body = self.analyz_body(blueprint.body, false);
         let ty = get_fn_type(&body);
 
         if !self.env.is_expected(&mangle, &ty) {
-            err!(
-                self,
-                ErrKind::InvaildType,
-                format!(
-                    "invaild return type for function {}, expected {} got {}",
-                    mangle,
-                    self.env.get(&mangle).unwrap().expected.as_ref().unwrap(),
-                    ty
-                )
+            // reported without returning so the function is still declared
+            let msg = format!(
+                "invaild return type for function {}, expected {} got {}",
+                mangle,
+                self.env.get(&mangle).unwrap().expected.as_ref().unwrap(),
+                ty
             );
+            self.report(ATErr::new(ErrKind::InvaildType, msg, self.span));
         }
 
         // let placeholder = AtomType {
@@ -360,8 +718,17 @@ impl Analyzer {
         // );
 
         self.env.parent();
-
-        self.env.push_function(mangle.clone(), func_type.clone());
+        self.span = parent_span;
+        self.file = parent_file;
+        self.constructing = parent_constructing;
+        self.loops = parent_loops;
+        self.lambdas = parent_lambdas;
+        self.module = parent_module;
+
+        // built functions are global, so a call from any scope reuses them
+        self.env
+            .top()
+            .push_function(mangle.clone(), func_type.clone());
 
         let func = Expr::Func {
             ret: ty,
@@ -376,19 +743,115 @@ impl Analyzer {
                 details: None,
             },
             expr: func,
+            span: blueprint.span,
         });
 
         Ok(mangle)
     }
 
+    // a lambda is a function value, the variables around it that it uses are copied into it when it is made
+    pub fn analyz_lambda(&mut self, params: Vec<Ident>, body: Vec<Node>) -> Result<Node, ErrKind> {
+        let mut typed_params = Vec::new();
+        for param in params {
+            let param = self.analyz_unknown_id(param)?;
+            // an untagged arg can be given anything
+            let ty = match param {
+                Ident::Typed(ref ty, _, _) => (**ty).clone(),
+                _ => AtomType {
+                    kind: AtomKind::Dynamic,
+                    details: None,
+                },
+            };
+            typed_params.push(Ident::Typed(
+                Box::new(ty),
+                param.val().clone(),
+                param.span(),
+            ));
+        }
+
+        // a break in a lambda cannot leave a loop around it
+        let parent_loops = std::mem::take(&mut self.loops);
+        self.env.child();
+        for param in &typed_params {
+            self.env.add(Symbol {
+                name: param.val().clone(),
+                ty: param.ty().clone(),
+                value: None,
+                expected: None,
+                mutable: true,
+                span: param.span(),
+            });
+        }
+
+        self.lambdas.push((self.env.depth(), Vec::new()));
+        let mut body = self.analyz_value_body(body, true);
+        let (_, captures) = self.lambdas.pop().unwrap();
+
+        self.env.parent();
+        self.loops = parent_loops;
+
+        // the value the body ends with is given back like a ret
+        let void = AtomType {
+            kind: AtomKind::Basic(BasicType::Void),
+            details: None,
+        };
+        if !diverges(&body) && body_value(&body) != void {
+            let last = body.pop().unwrap();
+            body.push(Node {
+                ty: last.ty.clone(),
+                span: last.span,
+                expr: Expr::RetExpr(Box::new(last)),
+            });
+        }
+
+        let ret = get_fn_type(&body);
+        if ret.kind == AtomKind::Unknown {
+            self.report(
+                ATErr::new(
+                    ErrKind::InvaildType,
+                    "this fn gives back values of different types".to_string(),
+                    self.span,
+                )
+                .note("give back the same type from every ret"),
+            );
+            return Err(ErrKind::InvaildType);
+        }
+
+        let ty = AtomType {
+            kind: AtomKind::Function(FunctionType {
+                params: typed_params
+                    .iter()
+                    .map(|param| param.ty().clone())
+                    .collect(),
+                return_type: Box::new(ret),
+            }),
+            details: None,
+        };
+
+        Ok(Node {
+            expr: Expr::Lambda {
+                params: typed_params,
+                body,
+                captures,
+            },
+            ty,
+            span: self.span,
+        })
+    }
+
     pub fn analyz_binary_expr(
         &mut self,
         left: Node,
         right: Node,
         op: String,
     ) -> Result<Node, ErrKind> {
-        let mut lhs = self.analyz(left)?;
-        let mut rhs = self.analyz(right)?;
+        let mut lhs = self.analyz(left);
+        let mut rhs = self.analyz(right);
+
+        // an operand already failed, dont report another error for it
+        if lhs.ty.is_error() || rhs.ty.is_error() {
+            return Ok(poison(self.span));
+        }
 
         if &rhs.ty.kind == &AtomKind::Unknown && &lhs.ty.kind == &AtomKind::Unknown {
         } else if let &AtomKind::Unknown = &rhs.ty.kind {
@@ -403,9 +866,27 @@ impl Analyzer {
             };
         }
 
-        (lhs, rhs) = self.unify_types(lhs, rhs)?;
+        // a shift gives the type of the value shifted, the count only has to be an int
+        if (op == "<<" || op == ">>") && is_int(&lhs.ty) {
+            self.shift_count(&rhs)?;
+            let ty = lhs.ty.clone();
+            let expr = Expr::BinaryExpr {
+                op,
+                left: Box::new(lhs),
+                right: Box::new(rhs),
+            };
+            return Ok(Node {
+                expr,
+                ty,
+                span: self.span,
+            });
+        }
+
+        let rhs = fit_literal(rhs, &lhs.ty);
+        let lhs = fit_literal(lhs, &rhs.ty);
+        let (lhs, rhs) = self.unify_types(lhs, rhs)?;
         let ty = match op.as_str() {
-            "==" | ">" | "<" | ">=" | "<=" => AtomType {
+            "==" | "!=" | ">" | "<" | ">=" | "<=" => AtomType {
                 kind: AtomKind::Basic(BasicType::Bool),
                 details: None,
             },
@@ -420,19 +901,119 @@ impl Analyzer {
         };
 
         if !supports_op(&lhs.ty, &op) {
-            err!(self,ErrKind::OperationNotGranted, format!("one of possible types [{:?}] does not support operator {}, use the do keyword to do it anyways", lhs.ty, op));
+            self.report(
+                ATErr::new(
+                    ErrKind::OperationNotGranted,
+                    format!("type {} does not support operator {}", lhs.ty, op),
+                    self.span,
+                )
+                .label(lhs.span, format!("this is of type {}", lhs.ty).as_str())
+                .note("use the do keyword to do it anyways"),
+            );
+            return Err(ErrKind::OperationNotGranted);
         }
         let left = Box::new(lhs);
         let right = Box::new(rhs);
 
         let expr = Expr::BinaryExpr { op, left, right };
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
+    }
+
+    fn shift_count(&mut self, count: &Node) -> Result<(), ErrKind> {
+        if is_int(&count.ty) || count.ty.kind == AtomKind::Unknown {
+            return Ok(());
+        }
+        self.report(
+            ATErr::new(
+                ErrKind::InvaildType,
+                format!("cannot shift by a {}, the count must be an int", count.ty),
+                self.span,
+            )
+            .label(count.span, format!("this is of type {}", count.ty).as_str()),
+        );
+        Err(ErrKind::InvaildType)
+    }
+
+    pub fn analyz_unary_expr(&mut self, op: String, operand: Node) -> Result<Node, ErrKind> {
+        let operand = self.analyz(operand);
+        if operand.ty.is_error() {
+            return Ok(poison(self.span));
+        }
+
+        if !operand.ty.get_unary_op().contains(&op.as_str()) {
+            self.report(
+                ATErr::new(
+                    ErrKind::OperationNotGranted,
+                    format!("type {} does not support unary operator {}", operand.ty, op),
+                    self.span,
+                )
+                .label(
+                    operand.span,
+                    format!("this is of type {}", operand.ty).as_str(),
+                ),
+            );
+            return Err(ErrKind::OperationNotGranted);
+        }
+
+        let ty = operand.ty.clone();
+        Ok(Node {
+            expr: Expr::UnaryExpr {
+                op,
+                operand: Box::new(operand),
+            },
+            ty,
+            span: self.span,
+        })
     }
 
     pub fn analyz_call(&mut self, name: Node, args: Vec<Node>) -> Result<Node, ErrKind> {
-        let name = Box::new(self.analyz(name)?);
+        // x.f: args calls f with x as the first argument
+        let name = if let Expr::MemberExpr { parent, child } = name.expr {
+            let parent = self.analyz(*parent);
+
+            if let Some((func, ty)) = self.member_fn(&parent.ty, &child) {
+                let args = [vec![parent], self.analyz_items(args)].concat();
+                if args.iter().any(|arg| arg.ty.is_error()) {
+                    return Ok(poison(self.span));
+                }
+
+                let args_types = args.iter().map(|arg| arg.ty.clone()).collect();
+                return match ty.clone().kind {
+                    AtomKind::Function(func_t) => {
+                        let name = Node {
+                            expr: Expr::Ident(Ident::UnTagged(func, name.span)),
+                            ty,
+                            span: name.span,
+                        };
+                        self.handle_function_call(Box::new(name), func_t, args, args_types)
+                    }
+                    AtomKind::Blueprint(blueprint_t) => {
+                        self.handle_blueprint_call(blueprint_t, args, args_types)
+                    }
+                    _ => unreachable!(),
+                };
+            }
+
+            let parent_span = self.span;
+            self.span = name.span;
+            let member = self.member_of(parent, child);
+            self.span = parent_span;
+
+            Box::new(member.unwrap_or_else(|_| poison(name.span)))
+        } else {
+            Box::new(self.analyz(name))
+        };
+
+        let args = self.analyz_items(args);
 
-        let args = self.analyz_items(args)?;
+        // the callee or an arg already failed, dont report another error for it
+        if name.ty.is_error() || args.iter().any(|arg| arg.ty.is_error()) {
+            return Ok(poison(self.span));
+        }
 
         let args_types: Vec<AtomType> = args.iter().map(|arg| arg.ty.clone()).collect();
         match name.ty.clone().kind {
@@ -458,7 +1039,6 @@ impl Analyzer {
         args_types: Vec<AtomType>,
     ) -> Result<Node, ErrKind> {
         let mangle = type_mangle(blueprint_t.name.clone(), args_types.clone());
-        // TODO! if its a member call pass parent as first arg and call the child instead
         // if &argc != &(args.len() as u32) {
         //     self.err(ErrKind::UndeclaredVar, format!("not enough arguments got {} arguments, expected {} arguments for function {:?}", args.len(), argc, name));
         //     return Err(ErrKind::UndeclaredVar);
@@ -472,12 +1052,14 @@ impl Analyzer {
                     return Ok(Node {
                         expr: Expr::FnCall {
                             name: Box::new(Node {
-                                expr: Expr::Ident(Ident::UnTagged(mangle)),
+                                expr: Expr::Ident(Ident::UnTagged(mangle, self.span)),
                                 ty: id_ty.clone(),
+                                span: self.span,
                             }),
                             args,
                         },
                         ty: *func.return_type.clone(),
+                        span: self.span,
                     })
                 }
 
@@ -501,12 +1083,17 @@ impl Analyzer {
         let expr = Expr::FnCall {
             name: Box::new(Node {
                 ty: ty.clone(),
-                expr: Expr::Ident(Ident::UnTagged(fun)),
+                expr: Expr::Ident(Ident::UnTagged(fun, self.span)),
+                span: self.span,
             }),
             args,
         };
 
-        Ok(Node { expr, ty: ret })
+        Ok(Node {
+            expr,
+            ty: ret,
+            span: self.span,
+        })
     }
 
     pub fn choose_overload(
@@ -518,7 +1105,7 @@ impl Analyzer {
         let mut blueprint = None;
         let mut possible = Vec::new();
 
-        for overload in blueprint_t.overloads {
+        for overload in blueprint_t.overloads.clone() {
             // if we got an exact overload no need to check for the best possible one to use
             if overload == mangle {
                 blueprint = Some(self.env.get_blueprint(&overload).unwrap());
@@ -527,14 +1114,19 @@ impl Analyzer {
                 break;
             }
 
+            // overloads taking a different amount of args can never match
+            if self.env.get_blueprint(&overload).unwrap().args.len() != args_types.len() {
+                continue;
+            }
+
             // make a list of possible overload that mangle could be from
             let mangle = mangle_types(mangle.clone());
 
             let mut found = true;
             for (i, ty) in mangle_types(overload.clone()).iter().enumerate() {
-                let m_ty = &mangle[i];
+                let m_ty = mangle.get(i);
 
-                if !(ty == m_ty || ty == &String::from("any")) {
+                if !(Some(ty) == m_ty || ty == &String::from("any")) {
                     found = false;
                     break;
                 }
@@ -565,6 +1157,45 @@ impl Analyzer {
                     choosen = name;
                 }
             }
+
+            if choosen.is_empty() {
+                let types: Vec<String> = args_types.iter().map(|ty| ty.to_string()).collect();
+                let name = blueprint_t.name.split('$').next().unwrap().to_string();
+                // new P: args takes the atom being built as self first, it is left out like in the call
+                if let Some(atom) = name.strip_suffix("dotnew") {
+                    let takes: Vec<String> = blueprint_t
+                        .overloads
+                        .iter()
+                        .map(|overload| {
+                            let blueprint = self.env.get_blueprint(overload).unwrap();
+                            let params: Vec<String> = blueprint.args[1..]
+                                .iter()
+                                .map(|arg| arg.ty().to_string())
+                                .collect();
+                            format!("({})", params.join(", "))
+                        })
+                        .collect();
+                    err!(
+                        self,
+                        ErrKind::UnexceptedArgs,
+                        format!(
+                            "new {} takes {}, got ({})",
+                            atom,
+                            takes.join(" or "),
+                            types[1..].join(", ")
+                        )
+                    );
+                }
+                err!(
+                    self,
+                    ErrKind::UnexceptedArgs,
+                    format!(
+                        "no overload of {} takes arguments ({})",
+                        name,
+                        types.join(", ")
+                    )
+                );
+            }
             blueprint = Some(self.env.get_blueprint(&choosen).unwrap());
         }
 
@@ -579,7 +1210,15 @@ impl Analyzer {
         args_types: Vec<AtomType>,
     ) -> Result<Node, ErrKind> {
         if func.params.len() != args.len() {
-            err!(self, ErrKind::UndeclaredVar, format!("not enough arguments got {} arguments, expected {} arguments for function {:?}", args.len(), args_types.len(), name));
+            err!(
+                self,
+                ErrKind::UnexceptedArgs,
+                format!(
+                    "expected {} arguments got {} arguments",
+                    func.params.len(),
+                    args_types.len()
+                )
+            );
         }
 
         for (i, arg) in (&mut args).iter_mut().enumerate() {
@@ -588,7 +1227,7 @@ impl Analyzer {
                     *arg = self.type_cast(arg.clone(), func.params[i].clone()).unwrap();
                 } else {
                     err!(
-                        self,
+                        self at arg.span,
                         ErrKind::UnexceptedArgs,
                         format!(
                             "unexpected argument type, at arg {}, expected {}, got {}",
@@ -599,25 +1238,37 @@ impl Analyzer {
             }
         }
 
-        let expr = Expr::FnCall { name, args };
+        // a function value is not a C function, it is called through the closure holding it
+        let expr = match name.expr {
+            Expr::Ident(ref id) if !self.is_var(id.val()) => Expr::FnCall { name, args },
+            _ => Expr::Invoke { callee: name, args },
+        };
 
         Ok(Node {
             expr,
             ty: *func.return_type,
+            span: self.span,
         })
     }
 
     pub fn analyz_index(&mut self, parent: Node, index: Node) -> Result<Node, ErrKind> {
-        let parent = self.analyz(parent)?;
-        let index = self.analyz(index)?;
+        let parent = self.analyz(parent);
+        let index = self.analyz(index);
+
+        if parent.ty.is_error() || index.ty.is_error() {
+            return Ok(poison(self.span));
+        }
 
         if index.ty.kind != AtomKind::Basic(BasicType::Int) {
-            err!(self, ErrKind::InvaildType, format!("index is not an int"));
+            err!(self at index.span, ErrKind::InvaildType, format!("index is not an int"));
         }
 
         let ty = match parent.ty.clone().kind {
             // until i add interfaces(traits) i have to do it manually
-            AtomKind::Atom(ref atom) if atom.name == types::Str.name => parent.ty.clone(), // str indexs into str not char for now
+            AtomKind::Atom(ref atom) if atom.name == types::Str.name => AtomType {
+                kind: AtomKind::Basic(BasicType::Char),
+                details: None,
+            },
             AtomKind::Atom(ref atom) if atom.name == types::List.name => atom.generics[0].clone(),
             _ => {
                 err!(
@@ -632,22 +1283,37 @@ impl Analyzer {
             parent: Box::new(parent),
             index: Box::new(index),
         };
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
     pub fn analyz_member(&mut self, parent: Node, child: String) -> Result<Node, ErrKind> {
-        let parent = self.analyz(parent)?;
+        let parent = self.analyz(parent);
+        self.member_of(parent, child)
+    }
 
-        let ty = parent.ty.get(&child);
-        let ty = if ty.is_none() {
-            let func = self.env.ty_parent_fn(&parent.ty, &child);
-            if func.is_none() {
-                return Err(ErrKind::UndeclaredVar);
-            }
+    // child of an already analyzed parent
+    pub fn member_of(&mut self, parent: Node, child: String) -> Result<Node, ErrKind> {
+        if parent.ty.is_error() {
+            return Ok(poison(self.span));
+        }
 
-            func.unwrap()
-        } else {
-            ty.unwrap().clone()
+        let ty = match self.atom(&parent.ty) {
+            Some(atom) => atom.fields.get(&child).cloned(),
+            None => parent.ty.get(&child).cloned(),
+        };
+        let ty = match ty {
+            Some(ty) => ty,
+            None => {
+                err!(
+                    self,
+                    ErrKind::UndeclaredVar,
+                    format!("{} has no member named {}", parent.ty, child)
+                );
+            }
         };
 
         let expr = Expr::MemberExpr {
@@ -655,39 +1321,113 @@ impl Analyzer {
             child,
         };
 
-        Ok(Node { ty, expr })
+        Ok(Node {
+            ty,
+            expr,
+            span: self.span,
+        })
+    }
+
+    pub fn analyz_new(&mut self, atom: Node, args: Vec<Node>) -> Result<Node, ErrKind> {
+        let atom = self.analyz(atom);
+        let args = self.analyz_items(args);
+
+        if atom.ty.is_error() || args.iter().any(|arg| arg.ty.is_error()) {
+            return Ok(poison(self.span));
+        }
+
+        let ty = match &atom.ty.kind {
+            AtomKind::Atom(ref declared) if atom.ty.is_type() && !declared.is_builtin() => {
+                AtomType {
+                    kind: atom.ty.kind.clone(),
+                    details: None,
+                }
+            }
+            _ => {
+                err!(
+                    self at atom.span,
+                    ErrKind::InvaildType,
+                    format!("cannot use new on {}, expected an atom", atom.ty)
+                );
+            }
+        };
+
+        let alloc = Node {
+            expr: Expr::NewExpr {
+                atom: Box::new(atom),
+                args: Vec::new(),
+            },
+            ty: ty.clone(),
+            span: self.span,
+        };
+
+        let new = match self.method(&ty, "new") {
+            Some(new) => new,
+            None if args.is_empty() => return Ok(alloc),
+            None => {
+                self.report(
+                    ATErr::new(
+                        ErrKind::UnexceptedArgs,
+                        format!("{} has no new method to take arguments", ty),
+                        self.span,
+                    )
+                    .note("declare one inside the atom with `set new: ...`"),
+                );
+                return Err(ErrKind::UnexceptedArgs);
+            }
+        };
+
+        let args = [vec![alloc], args].concat();
+        let args_types = args.iter().map(|arg| arg.ty.clone()).collect();
+        let call = self.handle_blueprint_call(new, args, args_types)?;
+
+        if call.ty != ty && !call.ty.is_error() {
+            err!(
+                self,
+                ErrKind::InvaildType,
+                format!("new method of {} cannot return a value", ty)
+            );
+        }
+
+        Ok(call)
     }
 
     pub fn analyz_unknown_id(&mut self, id: Ident) -> Result<Ident, ErrKind> {
         match &id {
-            &Ident::Tagged(ref tag, ref id) => {
-                let tag = self.analyz(*tag.clone())?;
+            &Ident::Tagged(ref tag, ref id, span) => {
+                let tag = self.analyz(*tag.clone());
                 let expr = tag.expr;
                 let tag = tag.ty;
 
+                // the tag already failed and was reported
+                if tag.is_error() {
+                    return Err(ErrKind::InvaildType);
+                }
+
                 // if tag has type details then it is a type, return tag type without the type details
                 if tag.is_type() {
                     return Ok(Ident::Typed(
-                        AtomType {
+                        Box::new(AtomType {
                             kind: tag.kind,
                             details: None,
-                        },
+                        }),
                         id.clone(),
+                        span,
                     ));
                 } else {
                     err!(
-                        self,
+                        self at span,
                         ErrKind::InvaildType,
                         format!("{:?} is not an Atom", expr)
                     );
                 }
             }
-            &Ident::Typed(_, _) | &Ident::UnTagged(_) => Ok(id),
+            &Ident::Typed(_, _, _) | &Ident::UnTagged(_, _) => Ok(id),
         }
     }
 
     pub fn analyz_id(&mut self, id: Ident) -> Result<Node, ErrKind> {
-        if let &Ident::Tagged(_, _) = &id {
+        if let &Ident::Tagged(_, _, _) = &id {
             err!(
                 self,
                 ErrKind::InvaildType,
@@ -698,23 +1438,89 @@ impl Analyzer {
             );
         }
 
+        let mut id = id;
+        *id.val_mut() = self.env.resolve(id.val(), &self.module);
+
         if !self.env.has(&id.val()) {
-            dbg!(id);
+            if let Some((module, name)) = id.val().rsplit_once("::") {
+                let msg = if self.mods.contains_key(module) {
+                    format!("{} has no member named {}", module, name)
+                } else {
+                    format!("cannot find mod {}", module)
+                };
+                err!(self, ErrKind::UndeclaredVar, msg);
+            }
+
+            self.report(
+                ATErr::new(
+                    ErrKind::UndeclaredVar,
+                    format!("cannot find {} in this scope", id.val()),
+                    self.span,
+                )
+                .note(format!("declare it first with `set {} = ...`", id.val()).as_str()),
+            );
             return Err(ErrKind::UndeclaredVar);
         }
 
         let ty = self.env.get_ty(&id.val()).unwrap();
+        self.capture(&id, &ty);
 
         let expr = Expr::Ident(id);
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
-    pub fn analyz_var_declare(&mut self, name: Ident, val: Node) -> Result<Node, ErrKind> {
-        let val = self.analyz(val)?;
+    // a variable used by a lambda but declared outside of it is captured by every lambda in between
+    fn capture(&mut self, id: &Ident, ty: &AtomType) {
+        let declared = match self.env.depth_of(id.val()) {
+            Some(depth) if self.is_var(id.val()) => depth,
+            _ => return,
+        };
+
+        for (depth, captures) in &mut self.lambdas {
+            if declared < *depth && !captures.iter().any(|capture| capture.val() == id.val()) {
+                captures.push(Ident::Typed(
+                    Box::new(ty.clone()),
+                    id.val().clone(),
+                    id.span(),
+                ));
+            }
+        }
+    }
+
+    // functions, blueprints and types are global, everything else lives in the function declaring it
+    fn is_var(&self, name: &String) -> bool {
+        self.env.is_local(name) || self.main_vars.contains(name)
+    }
+
+    pub fn analyz_var_declare(
+        &mut self,
+        name: Ident,
+        val: Node,
+        mutable: bool,
+    ) -> Result<Node, ErrKind> {
+        let val = self.analyz_value(val);
 
         if self.env.has(&name.val()) {
+            let declared = self.env.get(name.val()).unwrap().span;
+
+            self.report(
+                ATErr::new(
+                    ErrKind::VarAlreadyDeclared,
+                    format!("{} is already declared", name.val()),
+                    name.span(),
+                )
+                .label(declared, "declared here")
+                .note(format!("to change its value use `{} = ...`", name.val()).as_str()),
+            );
             return Err(ErrKind::VarAlreadyDeclared);
         }
+        if self.env.parent.is_none() {
+            self.main_vars.push(name.val().clone());
+        }
         self.env.add(Symbol {
             name: name.val().clone(),
             ty: AtomType {
@@ -723,12 +1529,19 @@ impl Analyzer {
             },
             value: None,
             expected: None,
+            mutable,
+            span: name.span(),
         });
 
-        self.expect(&name)?;
+        // an invaild tag is already reported, the variable is then left without an expected type
+        let _ = self.expect(&name);
+        let val = match self.env.get(name.val()).unwrap().expected.clone() {
+            Some(expected) => self.widen(val, &expected),
+            None => val,
+        };
         let ty = val.ty.clone();
 
-        if !self.env.is_expected(&name.val(), &ty) {
+        if !ty.is_error() && !self.env.is_expected(&name.val(), &ty) {
             err!(
                 self,
                 ErrKind::InvaildType,
@@ -746,23 +1559,59 @@ impl Analyzer {
                 ty: ty.clone(),
                 value: None,
                 expected: None,
+                mutable,
+                span: name.span(),
             },
         );
 
         let expr = Expr::VarDeclare {
             name,
             val: Box::new(val),
+            mutable,
         };
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
-    pub fn analyz_var_assign(&mut self, id: Node, val: Node) -> Result<Node, ErrKind> {
-        let val = self.analyz(val)?;
-        let name = self.analyz(id)?;
+    pub fn analyz_var_assign(
+        &mut self,
+        id: Node,
+        val: Node,
+        op: Option<String>,
+    ) -> Result<Node, ErrKind> {
+        let val = self.analyz_value(val);
+        let name = self.analyz(id);
+
+        if val.ty.is_error() || name.ty.is_error() {
+            return Ok(poison(self.span));
+        }
+        if let Some(op) = op {
+            return self.analyz_compound_assign(name, val, op);
+        }
+        let val = self.widen(val, &name.ty);
         let mut ty = val.ty.clone();
 
-        if let Expr::Ident(ref name) = name.expr {
-            self.env.modify_ty(&name.val(), ty.clone());
+        self.check_assignable(&name)?;
+
+        if let Expr::Ident(ref id) = name.expr {
+            // a variable keeps the type it was declared with, unless it was never known
+            if name.ty.kind == AtomKind::Unknown {
+                self.env.modify_ty(id.val(), ty.clone());
+            } else if val.ty != name.ty {
+                err!(
+                    self,
+                    ErrKind::InvaildType,
+                    format!(
+                        "cannot assign a value of type {} to {} of type {}",
+                        val.ty,
+                        id.val(),
+                        name.ty
+                    )
+                );
+            }
         } else if val.ty != name.ty {
             if name.ty.kind == AtomKind::Unknown {
                 ty = name.ty.clone();
@@ -774,78 +1623,787 @@ impl Analyzer {
         let expr = Expr::VarAssign {
             name: Box::new(name),
             val: Box::new(val),
+            op: None,
         };
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
+    // x += y is x = x + y with x only evaluated once, so x keeps its type
+    fn analyz_compound_assign(
+        &mut self,
+        name: Node,
+        val: Node,
+        op: String,
+    ) -> Result<Node, ErrKind> {
+        self.check_assignable(&name)?;
+
+        if !supports_op(&name.ty, &op) {
+            self.report(
+                ATErr::new(
+                    ErrKind::OperationNotGranted,
+                    format!("type {} does not support operator {}=", name.ty, op),
+                    self.span,
+                )
+                .label(name.span, format!("this is of type {}", name.ty).as_str()),
+            );
+            return Err(ErrKind::OperationNotGranted);
+        }
+
+        if name.ty.kind == AtomKind::Atom(types::Str.clone()) && op != "+" {
+            self.report(
+                ATErr::new(
+                    ErrKind::OperationNotGranted,
+                    format!("type str does not support operator {}=", op),
+                    self.span,
+                )
+                .label(name.span, "this is of type str")
+                .note("only += can change a str, it joins the value to the end"),
+            );
+            return Err(ErrKind::OperationNotGranted);
+        }
+
+        // the count of a shift only has to be an int, it is not converted to the type of x
+        let shift = (op == "<<" || op == ">>") && is_int(&name.ty);
+        let val = if shift {
+            self.shift_count(&val)?;
+            val
+        } else {
+            fit_literal(val, &name.ty)
+        };
+
+        let val = if shift || val.ty == name.ty || name.ty.kind == AtomKind::Unknown {
+            val
+        } else if can_implicitly_convert(&val.ty.kind, &name.ty.kind) {
+            ty_as(&name.ty, val)
+        } else {
+            self.report(
+                ATErr::new(
+                    ErrKind::InvaildType,
+                    format!(
+                        "cannot apply {}= to a {} with a value of type {}",
+                        op, name.ty, val.ty
+                    ),
+                    self.span,
+                )
+                .label(name.span, format!("this is of type {}", name.ty).as_str())
+                .label(val.span, format!("this is of type {}", val.ty).as_str()),
+            );
+            return Err(ErrKind::InvaildType);
+        };
+
+        let ty = name.ty.clone();
+        let expr = Expr::VarAssign {
+            name: Box::new(name),
+            val: Box::new(val),
+            op: Some(op),
+        };
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
+    }
+
+    // parts can only be set once, a part field only by the new method of its atom through self
+    fn check_assignable(&mut self, target: &Node) -> Result<(), ErrKind> {
+        match &target.expr {
+            Expr::Ident(id) if self.is_captured(id.val()) => {
+                let declared = self.env.get(id.val()).unwrap().span;
+                self.report(
+                    ATErr::new(
+                        ErrKind::OperationNotGranted,
+                        format!(
+                            "cannot assign to {}, this fn only has a copy of it",
+                            id.val()
+                        ),
+                        target.span,
+                    )
+                    .label(declared, "declared outside of the fn here")
+                    .note("give back the new value from the fn instead"),
+                );
+                Err(ErrKind::OperationNotGranted)
+            }
+
+            Expr::Ident(id) => {
+                let symbol = match self.env.get(id.val()) {
+                    Some(symbol) if !symbol.mutable => symbol.clone(),
+                    _ => return Ok(()),
+                };
+
+                self.report(
+                    ATErr::new(
+                        ErrKind::OperationNotGranted,
+                        format!("cannot assign to {}, it is a part", id.val()),
+                        target.span,
+                    )
+                    .label(symbol.span, "declared as a part here")
+                    .note("declare it with state to change it later"),
+                );
+                Err(ErrKind::OperationNotGranted)
+            }
+
+            Expr::MemberExpr { parent, child } => {
+                let atom = match self.atom(&parent.ty) {
+                    Some(atom) => atom,
+                    None => return Ok(()),
+                };
+
+                if atom.parts.contains(child) {
+                    let is_self = matches!(&parent.expr, Expr::Ident(id) if id.val() == "self");
+                    if is_self && self.constructing.as_ref() == Some(&atom.name) {
+                        return Ok(());
+                    }
+
+                    self.report(
+                        ATErr::new(
+                            ErrKind::OperationNotGranted,
+                            format!("cannot assign to {}, it is a part of {}", child, atom.name),
+                            target.span,
+                        )
+                        .note(
+                            format!(
+                                "parts can only be set in the new method of {}, declare it with state to change it later",
+                                atom.name
+                            )
+                            .as_str(),
+                        ),
+                    );
+                    return Err(ErrKind::OperationNotGranted);
+                }
+
+                // a bond is a value, changing one of its fields changes the bond itself
+                if atom.bond {
+                    return self.check_assignable(parent);
+                }
+                Ok(())
+            }
+
+            Expr::IndexExpr { parent, .. } if matches!(&parent.ty.kind, AtomKind::Atom(atom) if atom.name == types::Str.name) =>
+            {
+                self.report(
+                    ATErr::new(
+                        ErrKind::OperationNotGranted,
+                        "cannot assign to a char of a str".to_string(),
+                        target.span,
+                    )
+                    .note("strs cannot be changed in place, build a new one instead"),
+                );
+                Err(ErrKind::OperationNotGranted)
+            }
+
+            _ => Ok(()),
+        }
+    }
+
+    // declared outside of the innermost lambda around the node being analyzed
+    fn is_captured(&self, name: &String) -> bool {
+        match (self.lambdas.last(), self.env.depth_of(name)) {
+            (Some((depth, _)), Some(declared)) => declared < *depth && self.is_var(name),
+            _ => false,
+        }
+    }
+
+    // a used if gives the value of whichever branch runs, so it needs an else
     pub fn analyz_if_expr(
         &mut self,
         condition: Node,
         body: Vec<Node>,
         alt: Option<Node>,
+        used: bool,
     ) -> Result<Node, ErrKind> {
-        let condition = Box::new(self.analyz(condition)?);
+        let condition = Box::new(self.analyz(condition));
 
-        if condition.ty.kind != AtomKind::Basic(BasicType::Bool) {
+        if !condition.ty.is_error() && condition.ty.kind != AtomKind::Basic(BasicType::Bool) {
             err!(
-                self,
+                self at condition.span,
                 ErrKind::InvaildType,
                 format!(
-                    "invaild condition for while loop expected Bool got {:?}",
+                    "invaild condition for if expected bool got {}",
                     condition.ty
                 )
             );
         }
-        let body = self.analyz_body(body, false)?;
-
-        let analyzed_alt = if alt.is_none() {
-            None
-        } else {
-            Some(Box::new(self.analyz(alt.unwrap())?))
-        };
-
-        let last = body.last();
+        let mut body = self.analyz_value_body(body, used);
+        let mut alt = alt.map(|alt| Box::new(self.analyz_as(alt, used)));
 
-        let ty = if last.is_none() {
-            AtomType {
+        let ty = match alt.as_deref_mut() {
+            _ if !used => AtomType {
                 kind: AtomKind::Basic(BasicType::Void),
                 details: None,
+            },
+            Some(alt) => self.unify_branches(&mut body, alt)?,
+            None => {
+                let value = body_value(&body);
+                if value.kind != AtomKind::Basic(BasicType::Void)
+                    && !value.is_error()
+                    && !diverges(&body)
+                {
+                    self.report(
+                        ATErr::new(
+                            ErrKind::InvaildType,
+                            "an if without an else has no value when its condition is false"
+                                .to_string(),
+                            self.span,
+                        )
+                        .label(
+                            body.last().unwrap().span,
+                            format!("this gives {}", value).as_str(),
+                        )
+                        .note("add an else giving a value too"),
+                    );
+                    return Err(ErrKind::InvaildType);
+                }
+                value
             }
-        } else {
-            last.unwrap().ty.clone()
         };
 
         let expr = Expr::IfExpr {
             condition,
             body,
-            alt: analyzed_alt,
+            alt,
         };
 
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
-    pub fn analyz_while_expr(&mut self, condition: Node, body: Vec<Node>) -> Result<Node, ErrKind> {
-        let condition = Box::new(self.analyz(condition)?);
+    // a used body gives its last node as its value, the parser discarded it like any statement
+    fn analyz_value_body(&mut self, mut body: Vec<Node>, used: bool) -> Vec<Node> {
+        if let Some(last) = body.last_mut().filter(|_| used) {
+            if let Expr::Discard(value) = &last.expr {
+                *last = (**value).clone();
+            }
+        }
+        self.analyz_body(body, false)
+    }
 
-        if condition.ty.kind != AtomKind::Basic(BasicType::Bool) {
+    // the body of an if is a branch like its else
+    fn unify_branches(
+        &mut self,
+        body: &mut Vec<Node>,
+        alt: &mut Node,
+    ) -> Result<AtomType, ErrKind> {
+        let then = Node {
+            expr: Expr::Block(std::mem::take(body)),
+            ty: AtomType {
+                kind: AtomKind::Unknown,
+                details: None,
+            },
+            span: self.span,
+        };
+        let mut branches = [then, std::mem::replace(alt, poison(self.span))];
+        let ty = self.unify_arms(&mut branches, "if");
+
+        let [then, other] = branches;
+        if let Expr::Block(then) = then.expr {
+            *body = then;
+        }
+        *alt = other;
+        ty
+    }
+
+    // every branch of a used if or match gives a value, its type is the one they are unified into
+    fn unify_arms(&mut self, branches: &mut [Node], what: &str) -> Result<AtomType, ErrKind> {
+        let void = AtomType {
+            kind: AtomKind::Basic(BasicType::Void),
+            details: None,
+        };
+        let values: Vec<Option<AtomType>> = branches.iter().map(branch_value).collect();
+        if values.iter().flatten().any(AtomType::is_error) {
+            return Err(ErrKind::InvaildType);
+        }
+
+        // a branch that leaves gives nothing, the others give the value
+        let giving: Vec<usize> = (0..branches.len())
+            .filter(|&i| values[i].as_ref().is_some_and(|ty| ty != &void))
+            .collect();
+        let Some(&first) = giving.first() else {
+            return Ok(void);
+        };
+
+        if let Some(empty) = values.iter().position(|ty| ty.as_ref() == Some(&void)) {
+            let given = value_node(&mut branches[first]).span;
+            let nothing = value_node(&mut branches[empty]).span;
+            self.report(
+                ATErr::new(
+                    ErrKind::InvaildType,
+                    format!("not every branch of this {} gives a value", what),
+                    self.span,
+                )
+                .label(
+                    given,
+                    format!("this gives {}", values[first].as_ref().unwrap()).as_str(),
+                )
+                .label(nothing, "this gives nothing"),
+            );
+            return Err(ErrKind::InvaildType);
+        }
+
+        let mut unified = value_node(&mut branches[first]).clone();
+        let str_kind = AtomKind::Atom(types::Str.clone());
+        for &i in &giving[1..] {
+            let value = value_node(&mut branches[i]).clone();
+            // a branch is not turned into text to match the other ones
+            if (unified.ty.kind == str_kind) != (value.ty.kind == str_kind) {
+                self.report(
+                    ATErr::new(
+                        ErrKind::InvaildType,
+                        format!("the branches of this {} give different types", what),
+                        self.span,
+                    )
+                    .label(unified.span, format!("this gives {}", unified.ty).as_str())
+                    .label(value.span, format!("this gives {}", value.ty).as_str()),
+                );
+                return Err(ErrKind::InvaildType);
+            }
+            unified = self.unify_types(unified, value)?.1;
+        }
+        let ty = unified.ty;
+
+        for &i in &giving {
+            let value = value_node(&mut branches[i]);
+            if value.ty.kind != ty.kind {
+                let span = value.span;
+                *value = ty_as(&ty, std::mem::replace(value, poison(span)));
+            }
+            if let Expr::Block(_) = branches[i].expr {
+                branches[i].ty = ty.clone();
+            }
+        }
+        Ok(ty)
+    }
+
+    pub fn analyz_while_expr(
+        &mut self,
+        condition: Node,
+        body: Vec<Node>,
+        label: Option<String>,
+    ) -> Result<Node, ErrKind> {
+        let condition = Box::new(self.analyz(condition));
+
+        if !condition.ty.is_error() && condition.ty.kind != AtomKind::Basic(BasicType::Bool) {
             err!(
-                self,
+                self at condition.span,
                 ErrKind::InvaildType,
                 format!(
-                    "invaild condition for while loop expected Bool got {:?}",
+                    "invaild condition for while loop expected bool got {}",
                     condition.ty
                 )
             );
         }
-        let body = self.analyz_body(body, false)?;
+        let body = self.analyz_loop_body(body, &label)?;
+
+        let expr = Expr::WhileExpr {
+            condition,
+            body,
+            label,
+        };
+        let ty = AtomType {
+            kind: AtomKind::Basic(BasicType::Void),
+            details: None,
+        };
+
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
+    }
+
+    // the loop variable only lives in the body and cannot be assigned
+    pub fn analyz_for_expr(
+        &mut self,
+        var: Ident,
+        iter: Node,
+        body: Vec<Node>,
+        label: Option<String>,
+    ) -> Result<Node, ErrKind> {
+        let iter = self.analyz(iter);
+        if iter.ty.is_error() {
+            return Ok(poison(self.span));
+        }
+
+        let var_ty = match &iter.ty.kind {
+            _ if matches!(iter.expr, Expr::RangeExpr { .. }) => iter.ty.clone(),
+            AtomKind::Atom(atom) if atom.name == types::List.name => atom.generics[0].clone(),
+            _ => {
+                err!(
+                    self at iter.span,
+                    ErrKind::InvaildType,
+                    format!("cannot loop over {}, expected a range or a List", iter.ty)
+                );
+            }
+        };
+
+        self.check_new_name(&var, "give the loop variable another name")?;
+
+        self.env.child();
+        self.env.add(Symbol {
+            name: var.val().clone(),
+            ty: var_ty.clone(),
+            value: None,
+            expected: None,
+            mutable: false,
+            span: var.span(),
+        });
+        let body = self.analyz_loop_body(body, &label);
+        self.env.parent();
 
-        let expr = Expr::WhileExpr { condition, body };
+        let expr = Expr::ForExpr {
+            var: Ident::Typed(Box::new(var_ty), var.val().clone(), var.span()),
+            iter: Box::new(iter),
+            body: body?,
+            label,
+        };
         let ty = AtomType {
             kind: AtomKind::Basic(BasicType::Void),
             details: None,
         };
 
-        Ok(Node { expr, ty })
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
+    }
+
+    // names brought in by a loop or a match arm cannot hide another one
+    fn check_new_name(&mut self, var: &Ident, note: &str) -> Result<(), ErrKind> {
+        if let Some(declared) = self.env.get(var.val()) {
+            let declared = declared.span;
+            self.report(
+                ATErr::new(
+                    ErrKind::VarAlreadyDeclared,
+                    format!("{} is already declared", var.val()),
+                    var.span(),
+                )
+                .label(declared, "declared here")
+                .note(note),
+            );
+            return Err(ErrKind::VarAlreadyDeclared);
+        }
+        Ok(())
+    }
+
+    // arms are tried in order, so one after an arm fitting every value never runs
+    pub fn analyz_match_expr(
+        &mut self,
+        value: Node,
+        arms: Vec<(Pattern, Node)>,
+        used: bool,
+    ) -> Result<Node, ErrKind> {
+        let value = self.analyz(value);
+        if value.ty.is_error() {
+            return Ok(poison(self.span));
+        }
+
+        let mut patterns: Vec<Pattern> = vec![];
+        let mut bodies = vec![];
+        // the arm after which every value is matched, by its place in patterns
+        let mut every: Option<usize> = None;
+        let mut failed = false;
+
+        for (pattern, body) in arms {
+            let pattern = match self.analyz_pattern(pattern, &value.ty) {
+                Ok(pattern) => pattern,
+                Err(_) => {
+                    failed = true;
+                    continue;
+                }
+            };
+
+            // the first arm matching what this one does, it is the one that runs instead
+            let before = patterns
+                .iter()
+                .take(every.map_or(patterns.len(), |every| every + 1))
+                .position(|earlier| covers(earlier, &pattern))
+                .or(every)
+                .map(|before| patterns[before].span());
+            if let Some(before) = before {
+                self.report(
+                    ATErr::new(
+                        ErrKind::UnreachableArm,
+                        "this arm never runs".to_string(),
+                        pattern.span(),
+                    )
+                    .label(before, "the values it matches are already matched here"),
+                );
+                failed = true;
+            }
+
+            let catches_all = match &pattern {
+                Pattern::Wildcard(_) => true,
+                Pattern::Bind(id) => id.ty() == &value.ty,
+                _ => false,
+            };
+            patterns.push(pattern.clone());
+            // true and false, every type a Dynamic holds or ranges over every int fit every value too
+            let bools = [true, false].iter().all(|b| {
+                patterns.iter().any(
+                    |pattern| matches!(pattern, Pattern::Literal(Literal::Bool(v), _) if v == b),
+                )
+            });
+            let kinds = patterns
+                .iter()
+                .filter(|pattern| matches!(pattern, Pattern::Bind(_)))
+                .count();
+            let kinds = value.ty.kind == AtomKind::Dynamic && kinds == DYNAMIC_KINDS;
+            let ints = matches!(value.ty.kind, AtomKind::Basic(number) if number.is_int() && covers_ints(&patterns, number));
+            if every.is_none() && (catches_all || bools || kinds || ints) {
+                every = Some(patterns.len() - 1);
+            }
+
+            // the value of an arm is thrown away with the match
+            let body = match body.expr {
+                Expr::Block(_) => body,
+                _ if used => body,
+                _ => {
+                    let span = body.span;
+                    untyped(Expr::Discard(Box::new(body)), span)
+                }
+            };
+
+            self.env.child();
+            if let Pattern::Bind(ref id) = pattern {
+                if self
+                    .check_new_name(id, "give the matched value another name")
+                    .is_err()
+                {
+                    failed = true;
+                }
+                self.env.add(Symbol {
+                    name: id.val().clone(),
+                    ty: id.ty().clone(),
+                    value: None,
+                    expected: None,
+                    mutable: false,
+                    span: id.span(),
+                });
+            }
+            bodies.push(self.analyz_as(body, used));
+            self.env.parent();
+        }
+
+        if every.is_none() && !failed {
+            self.report(
+                ATErr::new(
+                    ErrKind::MissingArm,
+                    format!("this match does not cover every {}", value.ty),
+                    self.span,
+                )
+                .note("add a _ => arm for the rest"),
+            );
+            failed = true;
+        }
+
+        if failed {
+            return Err(ErrKind::MissingArm);
+        }
+
+        let ty = if used {
+            self.unify_arms(&mut bodies, "match")?
+        } else {
+            AtomType {
+                kind: AtomKind::Basic(BasicType::Void),
+                details: None,
+            }
+        };
+
+        let expr = Expr::MatchExpr {
+            value: Box::new(value),
+            arms: patterns.into_iter().zip(bodies).collect(),
+        };
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
+    }
+
+    // a pattern has to fit the matched type, a Dynamic value is matched by the type it holds
+    fn analyz_pattern(&mut self, pattern: Pattern, ty: &AtomType) -> Result<Pattern, ErrKind> {
+        match pattern {
+            Pattern::Wildcard(span) => Ok(Pattern::Wildcard(span)),
+            Pattern::Bind(id) => {
+                let id = match self.analyz_unknown_id(id)? {
+                    Ident::UnTagged(name, span) => Ident::Typed(Box::new(ty.clone()), name, span),
+                    id => id,
+                };
+
+                if id.ty() != ty && ty.kind == AtomKind::Dynamic && !held_by_dynamic(id.ty()) {
+                    self.report(
+                        ATErr::new(
+                            ErrKind::InvaildType,
+                            format!("a Dynamic value never holds a {}", id.ty()),
+                            id.span(),
+                        )
+                        .note("match one of int, i64, u64, float, f64, char, bool or str"),
+                    );
+                    return Err(ErrKind::InvaildType);
+                }
+                if id.ty() != ty && ty.kind != AtomKind::Dynamic {
+                    err!(self at id.span(), ErrKind::InvaildType, format!("a {} is never a {}", ty, id.ty()));
+                }
+                Ok(Pattern::Bind(id))
+            }
+            Pattern::Literal(literal, span) => {
+                self.check_pattern_literal(&literal, ty, span)?;
+                Ok(Pattern::Literal(literal, span))
+            }
+            Pattern::Range(start, end, span) => {
+                self.check_pattern_literal(&start, ty, span)?;
+
+                match (pattern_int(&start), pattern_int(&end)) {
+                    (Some(from), Some(to)) if from >= to => {
+                        err!(self at span, ErrKind::InvaildType, "this range is empty, its end is left out");
+                    }
+                    (Some(_), Some(_)) if ty.kind != AtomKind::Basic(BasicType::Bool) => {
+                        // the end is left out, so only the value before it has to fit
+                        let last = match end {
+                            Literal::Int(i, number) => Literal::Int(i - 1, number),
+                            ref end => end.clone(),
+                        };
+                        self.check_pattern_literal(&last, ty, span)?;
+                        Ok(Pattern::Range(start, end, span))
+                    }
+                    _ => {
+                        err!(self at span, ErrKind::InvaildType, "a range in a match holds ints or chars");
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_pattern_literal(
+        &mut self,
+        literal: &Literal,
+        ty: &AtomType,
+        span: Span,
+    ) -> Result<(), ErrKind> {
+        if ty.kind == AtomKind::Dynamic {
+            self.report(
+                ATErr::new(
+                    ErrKind::InvaildType,
+                    "a Dynamic value is matched by the type it holds".to_string(),
+                    span,
+                )
+                .note("match it with a name tagged with the type, like n@int"),
+            );
+            return Err(ErrKind::InvaildType);
+        }
+
+        let fits = match (literal, &ty.kind) {
+            (&Literal::Int(i, _), AtomKind::Basic(number)) if number.is_int() => {
+                if i < number.min() || i > number.max() {
+                    err!(self at span, ErrKind::InvaildType, format!("{} does not fit in {}", i, number));
+                }
+                true
+            }
+            // ints are written as floats
+            (Literal::Int(..) | Literal::Float(..), AtomKind::Basic(number)) => number.is_float(),
+            _ => &literal.get_ty() == ty,
+        };
+
+        if !fits {
+            err!(self at span, ErrKind::InvaildType, format!("cannot match a {} against a {}", ty, literal.get_ty()));
+        }
+        Ok(())
+    }
+
+    fn analyz_loop_body(
+        &mut self,
+        body: Vec<Node>,
+        label: &Option<String>,
+    ) -> Result<Vec<Node>, ErrKind> {
+        if label.is_some() && self.loops.contains(label) {
+            err!(
+                self,
+                ErrKind::VarAlreadyDeclared,
+                format!(
+                    "a loop around this one is already named {}",
+                    label.as_ref().unwrap()
+                )
+            );
+        }
+
+        self.loops.push(label.clone());
+        let body = self.analyz_body(body, false);
+        self.loops.pop();
+        Ok(body)
+    }
+
+    // break and continue only make sense inside a loop of the same function
+    fn analyz_jump(&mut self, node: Node, label: &Option<String>) -> Result<Node, ErrKind> {
+        let kw = match node.expr {
+            Expr::Break(_) => "break",
+            _ => "continue",
+        };
+
+        if self.loops.is_empty() {
+            err!(
+                self,
+                ErrKind::OperationNotGranted,
+                format!("{} outside of a loop", kw)
+            );
+        }
+        if let Some(label) = label {
+            if !self.loops.iter().any(|name| name.as_ref() == Some(label)) {
+                self.report(
+                    ATErr::new(
+                        ErrKind::UndeclaredVar,
+                        format!("cannot find a loop named {} around this {}", label, kw),
+                        self.span,
+                    )
+                    .note(format!("name a loop with while@{} or for@{}", label, label).as_str()),
+                );
+                return Err(ErrKind::UndeclaredVar);
+            }
+        }
+
+        Ok(Node {
+            expr: node.expr,
+            ty: AtomType {
+                kind: AtomKind::Basic(BasicType::Void),
+                details: None,
+            },
+            span: self.span,
+        })
+    }
+
+    // start..end counts from start up to end - 1, it has the type of the int it counts in
+    pub fn analyz_range(&mut self, start: Node, end: Node) -> Result<Node, ErrKind> {
+        let start = self.analyz(start);
+        let end = self.analyz(end);
+        if start.ty.is_error() || end.ty.is_error() {
+            return Ok(poison(self.span));
+        }
+
+        let (start, end) = self.unify_types(start, end)?;
+        if !matches!(start.ty.kind, AtomKind::Basic(number) if number.is_int()) {
+            err!(
+                self,
+                ErrKind::InvaildType,
+                format!("a range counts in ints, got {}", start.ty)
+            );
+        }
+
+        let ty = start.ty.clone();
+        let expr = Expr::RangeExpr {
+            start: Box::new(start),
+            end: Box::new(end),
+        };
+        Ok(Node {
+            expr,
+            ty,
+            span: self.span,
+        })
     }
 
     pub fn unify_types(&mut self, left: Node, right: Node) -> Result<(Node, Node), ErrKind> {
@@ -870,11 +2428,28 @@ impl Analyzer {
             return Ok((left, converted_right));
         }
 
-        err!(
-            self,
-            ErrKind::InvaildType,
-            format!("cannot unify types {} and {}", left.ty, right.ty)
+        self.report(
+            ATErr::new(
+                ErrKind::InvaildType,
+                format!("cannot unify types {} and {}", left.ty, right.ty),
+                self.span,
+            )
+            .label(left.span, format!("this is of type {}", left.ty).as_str())
+            .label(right.span, format!("this is of type {}", right.ty).as_str()),
         );
+        Err(ErrKind::InvaildType)
+    }
+
+    // a number stored into a wider number type or a value stored into a Dynamic, other values are left as they are
+    fn widen(&self, val: Node, into: &AtomType) -> Node {
+        let val = fit_literal(val, into);
+        match (&val.ty.kind, &into.kind) {
+            (AtomKind::Basic(from), AtomKind::Basic(number)) if from.widens_into(number) => {
+                ty_as(into, val)
+            }
+            (_, AtomKind::Dynamic) if held_by_dynamic(&val.ty) => ty_as(into, val),
+            _ => val,
+        }
     }
 
     pub fn type_cast(&mut self, from: Node, into: AtomType) -> Result<Node, ErrKind> {
@@ -1,54 +1,250 @@
 pub mod analysis;
 
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::vec;
 
+use indexmap::IndexMap;
+
 use crate::enviroment::{Enviroment, Symbol};
 
-use crate::err::ErrKind;
+use crate::err::{ATErr, ErrKind};
+use crate::lexer::token::Span;
 
-use crate::parser::ast::{Blueprint, Expr, Ident, Node};
+use crate::parser::ast::{AtomDef, Blueprint, Expr, Ident, Literal, ModDef, Node, Pattern};
 use crate::types::{
-    self, type_mangle, AtomDetails, AtomKind, AtomType, BasicType, BlueprintType, FunctionType,
+    self, mangle_types, type_mangle, Atom, AtomDetails, AtomKind, AtomType, BasicType,
+    BlueprintType, FunctionType,
 };
 
 pub struct Analyzer {
     workdir: String,
+    file: String,                 // file of the node being analyzed, used for errors
+    constructing: Option<String>, // atom whose new method is being analyzed
+    loops: Vec<Option<String>>, // labels of the loops around the node being analyzed, innermost last
+    lambdas: Vec<(usize, Vec<Ident>)>, // depth of the enviroment of each lambda around the node and the variables it captures, innermost last
+    main_vars: Vec<String>,            // variables declared at the top level, they live in main
+    module: String,                    // mod of the code being analyzed, empty outside of any mod
+    mods: IndexMap<String, Vec<String>>, // every mod path and the names declared in it
+    used: HashMap<PathBuf, String>,    // canonical path of every used file and the mod it declares
+    using: Vec<(PathBuf, String)>,     // files being analyzed, each one used by the one before it
     pub env: Enviroment,
     pub imports: Vec<Node>,   // Import nodes
+    pub atoms: Vec<Node>,     // Atom nodes
     pub functions: Vec<Node>, // Func nodes
-    line: u16,
-    column: u16,
+    span: Span,               // span of the node being analyzed, used for errors
+    pub errors: Vec<ATErr>,   // every error found while analyzing
 }
 
 impl AtomType {
     pub fn get_op(&self) -> &[&str] {
         match &self.kind {
-            &AtomKind::Basic(BasicType::Bool) => &["==", "||", "&&"],
-            &AtomKind::Basic(BasicType::Float) | &AtomKind::Basic(BasicType::Int) => {
-                &["+", "-", "*", "/", "%", "<", ">", "<=", ">=", "=="]
+            &AtomKind::Basic(BasicType::Bool) => &["==", "!=", "||", "&&"],
+            &AtomKind::Basic(BasicType::Char) => &["<", ">", "<=", ">=", "==", "!="],
+            &AtomKind::Basic(number) if number.is_int() => &[
+                "+", "-", "*", "/", "%", "<", ">", "<=", ">=", "==", "!=", "&", "|", "^", "<<",
+                ">>",
+            ],
+            &AtomKind::Basic(number) if number.is_float() => {
+                &["+", "-", "*", "/", "%", "<", ">", "<=", ">=", "==", "!="]
             }
             &AtomKind::Atom(ref atom)
                 if atom == &*types::Str || &atom.name == &*types::List.name =>
             {
-                &["<", ">", "==", "<=", ">=", "+", "-"]
+                &["<", ">", "==", "!=", "<=", ">=", "+", "-"]
             }
-            &AtomKind::Dynamic | &AtomKind::Unknown => &[
-                "&&", "||", "==", "<", ">", "<=", ">=", "+", "-", "*", "/", "%",
+            &AtomKind::Dynamic | &AtomKind::Unknown | &AtomKind::Error => &[
+                "&&", "||", "==", "!=", "<", ">", "<=", ">=", "+", "-", "*", "/", "%", "&", "|",
+                "^", "<<", ">>",
             ],
             _ => &[],
         }
     }
 }
 
+impl AtomType {
+    pub fn get_unary_op(&self) -> &[&str] {
+        match &self.kind {
+            &AtomKind::Basic(BasicType::Bool) => &["!"],
+            &AtomKind::Basic(number) if number.is_float() => &["-"],
+            &AtomKind::Basic(number) if number.is_signed() => &["-", "~"],
+            // negating an unsigned number would wrap around
+            &AtomKind::Basic(number) if number.is_int() => &["~"],
+            &AtomKind::Dynamic | &AtomKind::Unknown | &AtomKind::Error => &["-", "!", "~"],
+            _ => &[],
+        }
+    }
+}
+
 #[inline]
 pub fn ty_as(ty: &AtomType, expr: Node) -> Node {
+    let span = expr.span;
     Node {
         expr: Expr::As(Box::new(expr)),
         ty: ty.clone(),
+        span,
+    }
+}
+
+// an int literal without a suffix takes the int type it is used with, if its value fits in it
+pub fn fit_literal(val: Node, ty: &AtomType) -> Node {
+    match (&val.expr, &ty.kind) {
+        (&Expr::Literal(Literal::Int(i, BasicType::Int)), &AtomKind::Basic(number))
+            if number.is_int() && number.min() <= i && i <= number.max() =>
+        {
+            Node {
+                expr: Expr::Literal(Literal::Int(i, number)),
+                ty: ty.clone(),
+                span: val.span,
+            }
+        }
+        _ => val,
+    }
+}
+
+// stands in for a node that failed to analyze, the error is already reported
+#[inline]
+pub fn poison(span: Span) -> Node {
+    Node {
+        expr: Expr::Error,
+        ty: AtomType {
+            kind: AtomKind::Error,
+            details: None,
+        },
+        span,
+    }
+}
+
+// the mod a function or atom was declared in, from its full name
+pub fn module_of(name: &str) -> String {
+    let name = name.split('$').next().unwrap_or(name);
+    name.rsplit_once("::")
+        .map_or(String::new(), |(module, _)| module.to_string())
+}
+
+// the mod a used file declares, named after the file
+pub fn file_module(path: &str) -> String {
+    let stem = Path::new(path).file_stem().unwrap_or_default();
+    stem.to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+// a body ending in a ret, break or continue leaves before giving a value
+pub fn diverges(body: &[Node]) -> bool {
+    matches!(
+        body.last().map(|node| &node.expr),
+        Some(Expr::RetExpr(_) | Expr::Break(_) | Expr::Continue(_))
+    )
+}
+
+// the type a used body gives, a declaration or an assignment at its end gives nothing
+pub fn body_value(body: &[Node]) -> AtomType {
+    match body.last() {
+        Some(Node {
+            expr: Expr::VarDeclare { .. } | Expr::VarAssign { .. },
+            ..
+        })
+        | None => AtomType {
+            kind: AtomKind::Basic(BasicType::Void),
+            details: None,
+        },
+        Some(last) => last.ty.clone(),
     }
 }
 
+// the type a used branch of an if or a match gives, none for one that leaves first
+pub fn branch_value(branch: &Node) -> Option<AtomType> {
+    let body = match &branch.expr {
+        Expr::Block(block) => block.as_slice(),
+        _ => std::slice::from_ref(branch),
+    };
+    (!diverges(body)).then(|| body_value(body))
+}
+
+// the node giving the value of a branch, the last one of a block
+pub fn value_node(branch: &mut Node) -> &mut Node {
+    if matches!(&branch.expr, Expr::Block(block) if !block.is_empty()) {
+        if let Expr::Block(ref mut block) = branch.expr {
+            return block.last_mut().unwrap();
+        }
+    }
+    branch
+}
+
+// how many types a Dynamic value can hold
+pub const DYNAMIC_KINDS: usize = 8;
+
+// the types a Dynamic value can hold, the kind of its Obj tells which one
+pub fn held_by_dynamic(ty: &AtomType) -> bool {
+    match &ty.kind {
+        AtomKind::Basic(basic) => matches!(
+            basic,
+            BasicType::Int
+                | BasicType::I64
+                | BasicType::U64
+                | BasicType::Float
+                | BasicType::F64
+                | BasicType::Char
+                | BasicType::Bool
+        ),
+        AtomKind::Atom(atom) => atom == &*types::Str,
+        _ => false,
+    }
+}
+
+// the number a literal pattern stands for, ranges are made of them
+pub fn pattern_int(literal: &Literal) -> Option<i128> {
+    match *literal {
+        Literal::Int(i, _) => Some(i),
+        Literal::Char(c) => Some(c as i128),
+        Literal::Bool(b) => Some(b as i128),
+        _ => None,
+    }
+}
+
+// the numbers from start to end a pattern matches, a literal is a range of one
+pub fn pattern_range(pattern: &Pattern) -> Option<(i128, i128)> {
+    match pattern {
+        Pattern::Literal(literal, _) => pattern_int(literal).map(|i| (i, i + 1)),
+        Pattern::Range(start, end, _) => pattern_int(start).zip(pattern_int(end)),
+        _ => None,
+    }
+}
+
+// an arm with the later pattern never runs after one with the earlier pattern
+pub fn covers(earlier: &Pattern, later: &Pattern) -> bool {
+    match (earlier, later) {
+        (Pattern::Literal(a, _), Pattern::Literal(b, _)) if a == b => true,
+        (Pattern::Bind(a), Pattern::Bind(b)) => a.ty() == b.ty(),
+        _ => match (pattern_range(earlier), pattern_range(later)) {
+            (Some((start, end)), Some((from, to))) => start <= from && to <= end,
+            _ => false,
+        },
+    }
+}
+
+// true if the ranges and literals in patterns together match every value of an int type
+pub fn covers_ints(patterns: &[Pattern], number: BasicType) -> bool {
+    let mut ranges: Vec<(i128, i128)> = patterns.iter().filter_map(pattern_range).collect();
+    ranges.sort();
+    let mut next = number.min();
+    for (start, end) in ranges {
+        if start > next {
+            return false;
+        }
+        next = next.max(end);
+    }
+    next > number.max()
+}
+
 #[inline]
+pub fn is_int(ty: &AtomType) -> bool {
+    matches!(ty.kind, AtomKind::Basic(number) if number.is_int())
+}
+
 pub fn supports_op(ty: &AtomType, op: &String) -> bool {
     let ops = ty.get_op();
     ops.contains(&op.as_str())
@@ -71,7 +267,13 @@ fn get_ret_ty(node: &Node) -> Vec<AtomType> {
             ty
         }
 
-        Expr::WhileExpr { body, .. } | Expr::Block(body) => get_body_types(&body),
+        Expr::WhileExpr { body, .. } | Expr::ForExpr { body, .. } | Expr::Block(body) => {
+            get_body_types(&body)
+        }
+        Expr::Discard(node) => get_ret_ty(&node),
+        Expr::MatchExpr { arms, .. } => {
+            get_body_types(&arms.into_iter().map(|(_, arm)| arm).collect())
+        }
         // get fn ty => Block , ifBody
         _ => Vec::new(),
     }
@@ -128,17 +330,42 @@ pub fn get_fn_type(body: &Vec<Node>) -> AtomType {
     possible[0].clone()
 }
 impl Analyzer {
-    pub fn new(workdir: String) -> Self {
+    pub fn new(workdir: String, file: String) -> Self {
+        // the file being compiled can be used back by the files it uses
+        let using = fs::canonicalize(&file)
+            .map(|path| vec![(path, file.clone())])
+            .unwrap_or_default();
+
         Self {
             env: Enviroment::init(),
             functions: Vec::new(),
             imports: Vec::new(),
-            line: 0,
-            column: 0,
+            atoms: Vec::new(),
+            span: Span::default(),
             workdir,
+            file,
+            constructing: None,
+            loops: Vec::new(),
+            lambdas: Vec::new(),
+            main_vars: Vec::new(),
+            module: String::new(),
+            mods: IndexMap::new(),
+            used: HashMap::new(),
+            using,
+            errors: Vec::new(),
         }
     }
 
+    // records an error, errors are reported after analysis so we can keep going and find more
+    pub fn report(&mut self, err: ATErr) {
+        let err = if err.file.is_empty() {
+            err.in_file(&self.file)
+        } else {
+            err
+        };
+        self.errors.push(err);
+    }
+
     #[inline]
     fn import(
         &mut self,
@@ -163,6 +390,7 @@ impl Analyzer {
                 kind: AtomKind::Function(func),
                 details: None,
             },
+            span: Span::default(),
         })
     }
 
@@ -172,7 +400,7 @@ impl Analyzer {
 
     // expects a name as an ident tag if it has a tag
     pub fn expect_as(&mut self, name: &String, from: &Ident) -> Result<(), ErrKind> {
-        if let Ident::Tagged(_, _) = from {
+        if let Ident::Tagged(_, _, _) = from {
             let id = self.analyz_unknown_id(from.clone())?;
 
             self.env.expect(name, id.ty().clone());
@@ -182,7 +410,223 @@ impl Analyzer {
         }
     }
 
-    pub fn blueprints(&mut self, blueprints: Vec<Blueprint>) -> Result<(), ErrKind> {
+    pub fn mods(&mut self, mods: Vec<ModDef>) {
+        for module in mods {
+            if self.mods.contains_key(&module.path) {
+                self.report(ATErr::new(
+                    ErrKind::VarAlreadyDeclared,
+                    format!("mod {} is already declared", module.path),
+                    module.span,
+                ));
+                continue;
+            }
+            self.mods.insert(module.path, module.members);
+        }
+    }
+
+    pub fn atoms(&mut self, atoms: Vec<AtomDef>) {
+        let mut declared = Vec::new();
+
+        // every name is declared first so fields can be of any atom, including the one they are in
+        for atom in atoms {
+            if self.env.has(&atom.name) {
+                let declared = self.env.get(&atom.name).unwrap().span;
+                self.report(
+                    ATErr::new(
+                        ErrKind::VarAlreadyDeclared,
+                        format!("{} is already declared", atom.name),
+                        atom.span,
+                    )
+                    .label(declared, "declared here"),
+                );
+                continue;
+            }
+
+            self.env.add(Symbol {
+                name: atom.name.clone(),
+                ty: AtomType {
+                    kind: AtomKind::Atom(Atom {
+                        bond: atom.bond,
+                        ..Atom::new(atom.name.clone(), IndexMap::new(), IndexMap::new())
+                    }),
+                    details: Some(AtomDetails::Type),
+                },
+                value: None,
+                expected: None,
+                mutable: false,
+                span: atom.span,
+            });
+            declared.push(atom);
+        }
+
+        let mut nodes = Vec::new();
+        for atom in declared {
+            let mut fields = IndexMap::new();
+            let mut typed_fields = Vec::new();
+            // field types are found from inside the mod of the atom
+            let parent_module = std::mem::replace(&mut self.module, module_of(&atom.name));
+
+            for field in atom.fields {
+                // an invaild field type is already reported, the field is then left out
+                let id = match self.analyz_unknown_id(field) {
+                    Ok(id) => id,
+                    Err(_) => continue,
+                };
+
+                if fields.contains_key(id.val()) {
+                    self.report(ATErr::new(
+                        ErrKind::VarAlreadyDeclared,
+                        format!("field {} is already declared in {}", id.val(), atom.name),
+                        id.span(),
+                    ));
+                    continue;
+                }
+
+                fields.insert(id.val().clone(), id.ty().clone());
+                typed_fields.push(id);
+            }
+            self.module = parent_module;
+
+            self.env.modify_ty(
+                &atom.name,
+                AtomType {
+                    kind: AtomKind::Atom(Atom {
+                        parts: atom.parts,
+                        bond: atom.bond,
+                        ..Atom::new(atom.name.clone(), fields, IndexMap::new())
+                    }),
+                    details: Some(AtomDetails::Type),
+                },
+            );
+
+            nodes.push(Node {
+                expr: Expr::Atom {
+                    name: atom.name,
+                    fields: typed_fields,
+                },
+                ty: AtomType {
+                    kind: AtomKind::Basic(BasicType::Void),
+                    details: None,
+                },
+                span: atom.span,
+            });
+        }
+
+        // bonds are stored by value so they have to be declared before anything holding them
+        let mut done = Vec::new();
+        for i in 0..nodes.len() {
+            self.order_atom(i, &nodes, &mut done, &mut Vec::new());
+        }
+    }
+
+    fn order_atom(
+        &mut self,
+        i: usize,
+        nodes: &[Node],
+        done: &mut Vec<usize>,
+        path: &mut Vec<usize>,
+    ) {
+        if done.contains(&i) {
+            return;
+        }
+        let (name, fields) = match &nodes[i].expr {
+            Expr::Atom { name, fields } => (name, fields),
+            _ => unreachable!(),
+        };
+        if path.contains(&i) {
+            self.report(
+                ATErr::new(
+                    ErrKind::InvaildType,
+                    format!("bond {} contains itself", name),
+                    nodes[i].span,
+                )
+                .note("a bond is stored by value, use an atom to refer to it instead"),
+            );
+            done.push(i);
+            return;
+        }
+
+        path.push(i);
+        for field in fields {
+            if let AtomKind::Atom(atom) = &field.ty().kind {
+                let dep = nodes.iter().position(
+                    |node| matches!(&node.expr, Expr::Atom { name, .. } if *name == atom.name),
+                );
+                match dep {
+                    Some(dep) if atom.bond => self.order_atom(dep, nodes, done, path),
+                    _ => (),
+                }
+            }
+        }
+        path.pop();
+
+        if !done.contains(&i) {
+            done.push(i);
+            self.atoms.push(nodes[i].clone());
+        }
+    }
+
+    // the declared atom with its fields, the type of a field may only know the atom by name
+    pub fn atom(&self, ty: &AtomType) -> Option<Atom> {
+        match &ty.kind {
+            AtomKind::Atom(atom) if !atom.is_builtin() => match self.env.get_ty(&atom.name) {
+                Some(AtomType {
+                    kind: AtomKind::Atom(declared),
+                    details: Some(AtomDetails::Type),
+                }) if declared.name == atom.name => Some(declared),
+                _ => Some(atom.clone()),
+            },
+            _ => None,
+        }
+    }
+
+    // the blueprint of method name of an atom
+    pub fn method(&self, ty: &AtomType, name: &str) -> Option<BlueprintType> {
+        let atom = self.atom(ty)?;
+        match self.env.get_ty(&format!("{}dot{}", atom.name, name))?.kind {
+            AtomKind::Blueprint(blueprint) => Some(blueprint),
+            _ => None,
+        }
+    }
+
+    // the function or blueprint x.name: args calls with x as the first argument
+    // methods of the type of x come first then anything taking x as its first argument
+    pub fn member_fn(&self, ty: &AtomType, name: &str) -> Option<(String, AtomType)> {
+        if let AtomKind::Atom(atom) = &ty.kind {
+            let method = format!("{}dot{}", atom.name, name);
+            match self.env.get_ty(&method) {
+                Some(
+                    found @ AtomType {
+                        kind: AtomKind::Function(_) | AtomKind::Blueprint(_),
+                        ..
+                    },
+                ) => return Some((method, found)),
+                _ => (),
+            }
+        }
+
+        let name = self.env.resolve(name, &self.module);
+        let found = self.env.get_ty(&name)?;
+        let takes_ty = match &found.kind {
+            AtomKind::Function(func) => func.params.first() == Some(ty),
+            AtomKind::Blueprint(blueprint) => {
+                let first = &mangle_types(type_mangle(String::new(), vec![ty.clone()]))[0];
+                blueprint.overloads.iter().any(|overload| {
+                    let param = &mangle_types(overload.clone())[0];
+                    param == first || param == "any"
+                })
+            }
+            _ => false,
+        };
+
+        if takes_ty {
+            Some((name, found))
+        } else {
+            None
+        }
+    }
+
+    pub fn blueprints(&mut self, blueprints: Vec<Blueprint>) {
         let blueprints = &mut blueprints.clone();
 
         for blueprint in &mut *blueprints {
@@ -190,7 +634,10 @@ impl Analyzer {
             let mut types = Vec::new();
 
             for arg in blueprint.args.clone() {
-                let id = self.analyz_unknown_id(arg)?;
+                // an arg with an invaild tag is reported and treated as untagged
+                let id = self
+                    .analyz_unknown_id(arg.clone())
+                    .unwrap_or_else(|_| Ident::UnTagged(arg.val().clone(), arg.span()));
                 params.push(id.clone());
                 types.push(id.ty().clone());
             }
@@ -226,7 +673,19 @@ impl Analyzer {
                             }
                         }
 
-                        _ => panic!(),
+                        _ => {
+                            let declared = self.env.get(&ref_name).unwrap().span;
+                            self.report(
+                                ATErr::new(
+                                    ErrKind::VarAlreadyDeclared,
+                                    format!("{} is already declared", ref_name),
+                                    blueprint.span,
+                                )
+                                .in_file(&blueprint.file)
+                                .label(declared, "declared here"),
+                            );
+                            continue;
+                        }
                     }
                 }
             };
@@ -236,6 +695,8 @@ impl Analyzer {
                 ty: blueprint_ty,
                 value: None,
                 expected: None,
+                mutable: false,
+                span: blueprint.span,
             });
         }
 
@@ -246,6 +707,5 @@ impl Analyzer {
         //         self.analyz_blueprint(blueprint, Vec::new())?;
         //     }
         // }
-        Ok(())
     }
 }
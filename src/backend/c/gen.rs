@@ -1,9 +1,12 @@
 use core::panic;
 
-use super::{type_to_c, types_to_cnamed, Codegen, Emit, Item};
+use std::cell::RefCell;
+
+use super::{c_name, type_to_c, types_to_cnamed, Codegen, Emit, Emiter, Item};
 use crate::{
+    analysis::pattern_range,
     ir::{get_op_type, IROp},
-    parser::ast::Ident,
+    parser::ast::{Ident, Literal, Pattern},
     types::{self, AtomKind, AtomType, BasicType},
 };
 
@@ -23,6 +26,12 @@ impl Codegen {
             if let IROp::Import(_, module, _, _) = op {
                 self.module.include(module);
                 ir.remove(0);
+            } else if let IROp::Atom(name, fields) = op {
+                self.module.struct_add(
+                    name,
+                    fields.into_iter().map(|field| field.tuple()).collect(),
+                );
+                ir.remove(0);
             } else if let IROp::Def(ret, name, args, body) = op {
                 self.bond_fn(
                     name,
@@ -59,7 +68,7 @@ impl Codegen {
         let ty = type_to_c(ret);
         let args = types_to_cnamed(args);
         let mut emiter = self.emiter();
-        emiter.emit_header(format!("{} {}({}) {{", ty, name, args));
+        emiter.emit_header(format!("{} {}({}) {{", ty, c_name(&name), args));
         for op in body {
             let emit = self.bond(op);
 
@@ -99,6 +108,18 @@ impl Codegen {
             }
 
             IROp::Const(con) => self.push(Item::Const(con)),
+            IROp::New(ty) => {
+                if let AtomKind::Atom(ref atom) = ty.kind {
+                    // GC_malloc gives back zeroed memory so every field starts at zero
+                    // bonds live on the stack and get zeroed the same way
+                    let new = if atom.bond {
+                        format!("({}){{0}}", c_name(&atom.name))
+                    } else {
+                        self.call_one("__new__", c_name(&atom.name))
+                    };
+                    self.push(Item::Expr(ty, new));
+                }
+            }
             IROp::List(ty, items) => {
                 for item in items.clone() {
                     for expr in item {
@@ -121,24 +142,34 @@ impl Codegen {
             }
 
             IROp::LoadProp(ty, name) => {
+                let access = match self.borrow().get_ty().kind {
+                    AtomKind::Atom(atom) if atom.bond => ".",
+                    _ => "->",
+                };
                 let id = self.pop_str();
-                self.push(Item::Expr(ty, format!("{}->{}", id, name)));
+                self.push(Item::Expr(ty, format!("{}{}{}", id, access, name)));
             }
 
             IROp::LoadIdx(ty) => {
                 let idx = self.pop_str();
+                let is_str = self.borrow().get_ty().kind == AtomKind::Atom(types::Str.clone());
                 let expr = self.pop_str();
 
-                self.push(Item::Expr(
-                    ty.clone(),
-                    format!("__listget__({expr}, {}, {idx})", type_to_c(ty)),
-                ))
+                let get = if is_str {
+                    format!("__strget__({expr}, {idx})")
+                } else {
+                    format!("__listget__({expr}, {}, {idx})", type_to_c(ty.clone()))
+                };
+                self.push(Item::Expr(ty, get))
             }
 
             IROp::Call(ty, count) => {
                 let arg_count = count;
                 let name = self.pop_str();
-                let args = self.pop_amount(arg_count).join(", ");
+                // args were pushed in order so they come off the stack reversed
+                let mut args = self.pop_amount(arg_count);
+                args.reverse();
+                let args = args.join(", ");
                 let call = format!("{}({})", name, args);
                 if &ty.kind == &AtomKind::Basic(BasicType::Void) {
                     // our compiler only insert a line when the stack is empty, void functions doesnt push anything to the stack
@@ -147,8 +178,22 @@ impl Codegen {
                     self.push(Item::Expr(ty, call));
                 }
             }
-            IROp::While(body) => return self.bond_while(body),
-            IROp::If(_, body, alt) => return self.bond_if(body, alt),
+            IROp::Invoke(ty, count) => return self.bond_invoke(ty, count),
+            IROp::Lambda(ret, params, captures, body) => {
+                self.bond_lambda(ret, params, captures, body)
+            }
+            IROp::While(label, body) => return self.bond_while(label, body),
+            IROp::ForRange(ty, name, label, body) => {
+                return self.bond_for_range(ty, name, label, body)
+            }
+            IROp::ForEach(ty, name, label, body) => {
+                return self.bond_for_each(ty, name, label, body)
+            }
+            IROp::Break(label) => return self.bond_jump("break", label),
+            IROp::Continue(label) => return self.bond_jump("continue", label),
+            IROp::If(ty, body, alt) => return self.bond_if(ty, body, alt),
+            IROp::Block(ty, body) => return self.bond_block(ty, body),
+            IROp::Match(ty, value_ty, arms) => return self.bond_match(ty, value_ty, arms),
 
             IROp::Conv(into, from) => {
                 self.bond_conv(into, from);
@@ -178,37 +223,241 @@ impl Codegen {
                     return Emit::Line(format!("{} = {}", name, val));
                 }
             }
+            IROp::Update(op) => return self.bond_update(*op),
             IROp::Ret(_) => {
                 let val = self.pop_str();
                 return Emit::Line(format!("return {}", val));
             }
+            IROp::Neg(_) | IROp::Not | IROp::BitNot(_) => self.bond_unary(op),
             _ => return self.bond_binary(op), // attempt to bond binary expr instead
         }
         Emit::None
     }
-    fn bond_while(&mut self, body: Vec<IROp>) -> Emit {
+    // a lambda is a C function taking its captures as the first arg, the closure holds both
+    fn bond_lambda(
+        &mut self,
+        ret: AtomType,
+        params: Vec<Ident>,
+        captures: Vec<Ident>,
+        body: Vec<IROp>,
+    ) {
+        let fn_ty = get_op_type(&IROp::Lambda(
+            ret.clone(),
+            params.clone(),
+            captures.clone(),
+            vec![],
+        ));
+        self.temp_count += 1;
+        let name = format!("__fn_{}", self.temp_count);
+        let env = format!("{}__env", name);
+
+        let captures: Vec<(AtomType, String)> = captures
+            .into_iter()
+            .map(|capture| (capture.ty().clone(), self.get_var(capture.val().clone())))
+            .collect();
+        let params = types_to_cnamed(params.into_iter().map(|param| param.tuple()).collect());
+        let sep = if params.is_empty() { "" } else { ", " };
+
+        // the function is hoisted out of the one making the lambda
+        let mut emiter = Emiter::new(RefCell::new(0));
+        emiter.emit_header(format!(
+            "{} {}(void *__env{}{}) {{",
+            type_to_c(ret),
+            name,
+            sep,
+            params
+        ));
+        for (ty, capture) in &captures {
+            emiter.emit(format!(
+                "{} {capture} = (({env} *)__env)->{capture}",
+                type_to_c(ty.clone())
+            ));
+        }
+        let stack = std::mem::take(&mut self.stack);
+        for op in body {
+            let emit = self.bond(op);
+
+            emiter.embed(emit);
+        }
+        self.stack = stack;
+        emiter.end();
+        self.module.func(emiter.finish());
+
+        // a lambda capturing nothing needs no enviroment
+        let closure = if captures.is_empty() {
+            format!("__closure__({}, NULL)", name)
+        } else {
+            self.module.struct_add(env.clone(), captures.clone());
+            let sets: Vec<String> = captures
+                .iter()
+                .map(|(_, capture)| format!("__env->{capture} = {capture}; "))
+                .collect();
+            format!(
+                "({{ {env} *__env = __new__({env}); {}__closure__({name}, __env); }})",
+                sets.concat()
+            )
+        };
+        self.push(Item::Expr(fn_ty, closure));
+    }
+
+    // the function of a closure is cast back to its C type before it is called
+    fn bond_invoke(&mut self, ty: AtomType, count: u16) -> Emit {
+        let callee = self.pop();
+        let params = match callee.get_ty().kind {
+            AtomKind::Function(func) => func.params,
+            _ => unreachable!(),
+        };
+        let (is_var, callee) = match callee {
+            Item::Var(_, name) => (true, name),
+            Item::Expr(_, expr) => (false, expr),
+            _ => unreachable!(),
+        };
+
+        let mut args = self.pop_amount(count);
+        args.reverse();
+        let fn_ty = [
+            vec!["void *".to_string()],
+            params.into_iter().map(type_to_c).collect(),
+        ]
+        .concat()
+        .join(", ");
+        let closure = if is_var { callee.as_str() } else { "__closure" };
+        let call = format!(
+            "(({} (*)({})){closure}->fn)({})",
+            type_to_c(ty.clone()),
+            fn_ty,
+            [vec![format!("{closure}->env")], args].concat().join(", ")
+        );
+        // the closure is only worked out once
+        let call = if is_var {
+            call
+        } else {
+            format!("({{ Closure *__closure = {callee}; {call}; }})")
+        };
+
+        if ty.kind == AtomKind::Basic(BasicType::Void) {
+            return Emit::Line(call);
+        }
+        self.push(Item::Expr(ty, call));
+        Emit::None
+    }
+
+    fn bond_while(&mut self, label: Option<String>, body: Vec<IROp>) -> Emit {
         let mut emiter = self.emiter();
         let cond = self.pop_str();
 
         emiter.emit_header(format!("while ({}) {{", cond));
+        let label = self.bond_loop_body(&mut emiter, label, body);
+        emiter.end();
+        Self::break_label(&mut emiter, label);
+        Emit::Body(emiter.finish())
+    }
+
+    // end is evaluated once before the first iteration
+    fn bond_for_range(
+        &mut self,
+        ty: AtomType,
+        name: String,
+        label: Option<String>,
+        body: Vec<IROp>,
+    ) -> Emit {
+        let mut emiter = self.emiter();
+        let end = self.pop_str();
+        let start = self.pop_str();
+        let tyc = type_to_c(ty);
+
+        emiter.emit_header(format!(
+            "for ({tyc} {name} = {start}, {name}__end = {end}; {name} < {name}__end; {name}++) {{"
+        ));
+        let label = self.bond_loop_body(&mut emiter, label, body);
+        emiter.end();
+        Self::break_label(&mut emiter, label);
+        Emit::Body(emiter.finish())
+    }
+
+    // a named loop gets a c label to continue at the end of its body and one to break after it
+    fn bond_loop_body(
+        &mut self,
+        emiter: &mut Emiter,
+        label: Option<String>,
+        body: Vec<IROp>,
+    ) -> Option<String> {
+        let label = label.map(|label| {
+            self.label_count += 1;
+            let c_label = format!("{}__{}", label, self.label_count);
+            self.labels.push((label, c_label.clone()));
+            c_label
+        });
+
         for expr in body {
             let emit = self.bond(expr);
 
             emiter.embed(emit);
         }
 
+        if let Some(ref label) = label {
+            self.labels.pop();
+            emiter.lines(vec![format!("{}__continue:;", label)]);
+        }
+        label
+    }
+
+    fn break_label(emiter: &mut Emiter, label: Option<String>) {
+        if let Some(label) = label {
+            emiter.lines(vec![format!("{}__break:;", label)]);
+        }
+    }
+
+    fn bond_jump(&mut self, kw: &str, label: Option<String>) -> Emit {
+        let label = label.and_then(|label| {
+            self.labels
+                .iter()
+                .rev()
+                .find(|(name, _)| name == &label)
+                .map(|(_, c_label)| c_label.clone())
+        });
+
+        match label {
+            Some(label) => Emit::Line(format!("goto {}__{}", label, kw)),
+            None => Emit::Line(kw.to_string()),
+        }
+    }
+
+    // the list and its size are read once, pushing to it inside the loop does not make it longer
+    fn bond_for_each(
+        &mut self,
+        ty: AtomType,
+        name: String,
+        label: Option<String>,
+        body: Vec<IROp>,
+    ) -> Emit {
+        let mut emiter = self.emiter();
+        let list = self.pop_str();
+        let tyc = type_to_c(ty);
+
+        emiter.emit_header("{");
+        emiter.emit(format!("List *{name}__list = {list}"));
+        emiter.emit(format!("size_t {name}__size = {name}__list->size"));
+        emiter.emit_header(format!(
+            "for (size_t {name}__i = 0; {name}__i < {name}__size; {name}__i++) {{"
+        ));
+        emiter.emit(format!(
+            "{tyc} {name} = __listget__({name}__list, {tyc}, {name}__i)"
+        ));
+        let label = self.bond_loop_body(&mut emiter, label, body);
+        emiter.end();
+        Self::break_label(&mut emiter, label);
         emiter.end();
         Emit::Body(emiter.finish())
     }
 
-    fn bond_if(&mut self, body: Vec<IROp>, alt: Vec<IROp>) -> Emit {
+    fn bond_if(&mut self, ty: AtomType, body: Vec<IROp>, alt: Vec<IROp>) -> Emit {
         let mut emiter = self.emiter();
 
         let cond = self.pop_str();
+        let temp = self.temp(&mut emiter, &ty, "if");
         emiter.emit_header(format!("if ({}) {{", cond));
-        for expr in body {
-            let emit = self.bond(expr);
-
+        for emit in self.bond_branch(body, &temp) {
             emiter.embed(emit);
         }
 
@@ -216,8 +465,7 @@ impl Codegen {
 
         if alt.len() > 0 {
             let mut compiled_alt = vec![];
-            for expr in alt {
-                let emit = self.bond(expr);
+            for emit in self.bond_branch(alt, &temp) {
                 match emit {
                     Emit::Body(lines) => {
                         for line in lines {
@@ -239,32 +487,231 @@ impl Codegen {
             }
         }
 
+        if let Some(temp) = temp {
+            self.push(Item::Var(ty, temp));
+        }
         Emit::Body(emiter.finish())
     }
 
+    // a block gets its own braces so its variables stay in it
+    // a block giving a value is a statement expression, a while condition reruns all of it
+    fn bond_block(&mut self, ty: AtomType, body: Vec<IROp>) -> Emit {
+        if ty.kind == AtomKind::Basic(BasicType::Void) {
+            let mut emiter = self.emiter();
+            emiter.emit_header("{");
+            for emit in self.bond_branch(body, &None) {
+                emiter.embed(emit);
+            }
+            emiter.end();
+            return Emit::Body(emiter.finish());
+        }
+
+        let depth = self.stack.len();
+        let mut stmts = vec![];
+        for emit in body.into_iter().map(|op| self.bond(op)) {
+            match emit {
+                Emit::Body(lines) => stmts.extend(lines.iter().map(|line| line.trim().to_string())),
+                Emit::Line(line) => stmts.push(format!("{};", line)),
+                Emit::None => (),
+            }
+        }
+        if self.stack.len() > depth {
+            stmts.push(format!("{};", self.pop_str()));
+        }
+        self.push(Item::Expr(ty, format!("({{ {} }})", stmts.join(" "))));
+        Emit::None
+    }
+
+    // a match becomes a switch when c allows one, a chain of ifs otherwise
+    fn bond_match(
+        &mut self,
+        ty: AtomType,
+        value_ty: AtomType,
+        arms: Vec<(Pattern, Vec<IROp>)>,
+    ) -> Emit {
+        let mut emiter = self.emiter();
+
+        let value = self.pop_str();
+        let temp = self.temp(&mut emiter, &ty, "match");
+        // the matched value is only evaluated once
+        self.temp_count += 1;
+        let matched = format!("__matched_{}", self.temp_count);
+        emiter.lines(vec![format!(
+            "{} {} = {};",
+            type_to_c(value_ty.clone()),
+            matched,
+            value
+        )]);
+
+        if switchable(&value_ty, &arms) {
+            // a Dynamic value is switched over by the kind of value it holds
+            let dynamic = value_ty.kind == AtomKind::Dynamic;
+            let subject = if dynamic {
+                format!("{}.kind", matched)
+            } else {
+                matched.clone()
+            };
+
+            emiter.emit_header(format!("switch ({}) {{", subject));
+            for (pattern, body) in arms {
+                let case = match &pattern {
+                    Pattern::Literal(literal, _) => format!("case {}:", self.c_literal(literal)),
+                    Pattern::Range(start, end, _) => format!(
+                        "case {} ... {}:",
+                        self.c_literal(start),
+                        self.c_literal(&before(end))
+                    ),
+                    Pattern::Bind(id) if id.ty() != &value_ty => {
+                        format!("case {}:", obj_kind(id.ty()).0)
+                    }
+                    _ => "default:".to_string(),
+                };
+
+                emiter.emit_header(format!("{} {{", case));
+                self.bond_arm(&mut emiter, &pattern, body, &matched, &value_ty, &temp);
+                emiter.lines(vec!["break;".to_string()]);
+                emiter.end();
+            }
+            emiter.end();
+        } else {
+            for (i, (pattern, body)) in arms.into_iter().enumerate() {
+                let header = match (i, self.pattern_test(&pattern, &matched, &value_ty)) {
+                    (0, Some(test)) => format!("if ({}) {{", test),
+                    (_, Some(test)) => format!("else if ({}) {{", test),
+                    (0, None) => "{".to_string(),
+                    (_, None) => "else {".to_string(),
+                };
+
+                emiter.emit_header(header);
+                self.bond_arm(&mut emiter, &pattern, body, &matched, &value_ty, &temp);
+                emiter.end();
+            }
+        }
+
+        if let Some(temp) = temp {
+            self.push(Item::Var(ty, temp));
+        }
+        Emit::Body(emiter.finish())
+    }
+
+    // an arm naming the value declares it first, a Dynamic one as the type it holds
+    fn bond_arm(
+        &mut self,
+        emiter: &mut Emiter,
+        pattern: &Pattern,
+        body: Vec<IROp>,
+        matched: &str,
+        value_ty: &AtomType,
+        temp: &Option<String>,
+    ) {
+        if let Pattern::Bind(id) = pattern {
+            let value = if id.ty() != value_ty {
+                format!("{}.val.{}", matched, obj_kind(id.ty()).1)
+            } else {
+                matched.to_string()
+            };
+            emiter.lines(vec![format!(
+                "{} {} = {};",
+                type_to_c(id.ty().clone()),
+                id.val(),
+                value
+            )]);
+        }
+
+        for emit in self.bond_branch(body, temp) {
+            emiter.embed(emit);
+        }
+    }
+
+    // the condition under which an arm runs, none for an arm matching every value
+    fn pattern_test(
+        &mut self,
+        pattern: &Pattern,
+        matched: &str,
+        value_ty: &AtomType,
+    ) -> Option<String> {
+        match pattern {
+            Pattern::Literal(literal, _) if value_ty.kind == AtomKind::Atom(types::Str.clone()) => {
+                Some(format!(
+                    "__streq__({}, {})",
+                    matched,
+                    self.c_literal(literal)
+                ))
+            }
+            Pattern::Literal(literal, _) => {
+                Some(format!("{} == {}", matched, self.c_literal(literal)))
+            }
+            Pattern::Range(start, end, _) => Some(format!(
+                "{} >= {} && {} < {}",
+                matched,
+                self.c_literal(start),
+                matched,
+                self.c_literal(end)
+            )),
+            Pattern::Bind(id) if id.ty() != value_ty => {
+                Some(format!("{}.kind == {}", matched, obj_kind(id.ty()).0))
+            }
+            _ => None,
+        }
+    }
+
+    fn c_literal(&mut self, literal: &Literal) -> String {
+        self.push(Item::Const(literal.clone()));
+        self.pop_str()
+    }
+
+    // declares the variable an if or a block giving a value stores it in
+    fn temp(&mut self, emiter: &mut Emiter, ty: &AtomType, name: &str) -> Option<String> {
+        if ty.kind == AtomKind::Basic(BasicType::Void) {
+            return None;
+        }
+
+        self.temp_count += 1;
+        let temp = format!("__{}_{}", name, self.temp_count);
+        emiter.lines(vec![format!("{} {};", type_to_c(ty.clone()), temp)]);
+        Some(temp)
+    }
+
+    // a branch leaving a value on the stack stores it in the temporary, one that left the loop or function gave none
+    fn bond_branch(&mut self, body: Vec<IROp>, temp: &Option<String>) -> Vec<Emit> {
+        let depth = self.stack.len();
+        let mut emits: Vec<Emit> = body.into_iter().map(|op| self.bond(op)).collect();
+
+        if let Some(temp) = temp {
+            if self.stack.len() > depth {
+                emits.push(Emit::Line(format!("{} = {}", temp, self.pop_str())));
+            }
+        }
+        emits
+    }
+
     #[inline]
     fn genbinary(&mut self, op: &str, ty: AtomType) -> Item {
-        if &self.borrow().get_ty().kind == &AtomKind::Atom(types::Str.clone()) {
+        let is_str = &self.borrow().get_ty().kind == &AtomKind::Atom(types::Str.clone());
+        // the left operand was pushed first
+        let rhs = self.pop_str();
+        let lhs = self.pop_str();
+        if is_str {
             let binop = match op {
                 "+" => "__stradd__",
                 "-" => "__strsub__",
                 "==" => "__streq__",
-                ">" => "__strcomp__",
-                ">=" => "__strecomp__",
+                "!=" => "__strneq__",
+                "<" => "__strlt__",
+                "<=" => "__strle__",
+                ">" => "__strgt__",
+                ">=" => "__strge__",
                 _ => panic!(),
             };
-            Item::Expr(
-                ty,
-                format!("{}({}, {})", binop, self.pop_str(), self.pop_str()),
-            )
+            Item::Expr(ty, format!("{}({}, {})", binop, lhs, rhs))
         } else {
-            Item::Expr(ty, format!("{} {} {}", self.pop_str(), op, self.pop_str()))
+            Item::Expr(ty, format!("({} {} {})", lhs, op, rhs))
         }
     }
     #[inline]
-    fn binary(&mut self, op: &str) -> Item {
-        let ty = self.borrow().get_ty();
-        self.genbinary(op, ty)
+    fn binary(&mut self, op: &str, ty: AtomType) -> Item {
+        let item = self.genbinary(op, ty);
+        narrow(item)
     }
 
     #[inline]
@@ -282,55 +729,83 @@ impl Codegen {
         let item = if get_op_type(&op).kind == AtomKind::Dynamic
             || self.borrow().get_ty().kind == AtomKind::Dynamic
         {
-            let ops = vec![self.pop_str(), self.pop_str()];
+            let rhs = self.pop_str();
+            let ops = vec![self.pop_str(), rhs];
             Item::Expr(
                 AtomType {
                     kind: AtomKind::Dynamic,
                     details: None,
                 },
-                self.call(
-                    match op {
-                        IROp::Add(_) => "__add__",
-                        IROp::Sub(_) => "__sub__",
-                        IROp::Mul(_) => "__mul__",
-                        IROp::Div(_) => "__div__",
-                        IROp::Mod(_) => "__mod__",
-                        IROp::Comp => "__comp__",
-                        IROp::EComp => "__ecomp__",
-                        IROp::Eq => "__eq__",
-                        IROp::And => "__and__",
-                        IROp::Or => "__or__",
-                        _ => todo!(),
-                    },
-                    ops,
-                ),
+                self.call(dynamic_op(&op), ops),
             )
         } else {
             match op {
-                IROp::Add(_) => self.binary("+"),
-                IROp::Sub(_) => self.binary("-"),
-                IROp::Mul(_) => self.binary("*"),
-                IROp::Div(_) => self.binary("/"),
-                IROp::Mod(_) => self.binary("%"),
-                IROp::Comp => self.binaryb(">"),
-                IROp::Eq => self.binaryb("=="),
-                IROp::EComp => self.binaryb(">="),
-                IROp::And => self.binaryb("&&"),
-                IROp::Or => self.binaryb("||"),
-                _ => todo!("unimplented op {:#?}", op),
+                IROp::Lt
+                | IROp::Le
+                | IROp::Gt
+                | IROp::Ge
+                | IROp::Eq
+                | IROp::NotEq
+                | IROp::And
+                | IROp::Or => self.binaryb(c_op(&op)),
+                // a shift by a wider count still gives the type of the value shifted
+                _ => self.binary(c_op(&op), get_op_type(&op)),
             }
         };
         self.push(item);
         Emit::None
     }
 
+    // the target is only evaluated once, strs and dynamic values go through __update__
+    fn bond_update(&mut self, op: IROp) -> Emit {
+        let val = self.pop_str();
+        let target = self.pop_str();
+        let ty = get_op_type(&op);
+
+        let func = match &ty.kind {
+            AtomKind::Dynamic => dynamic_op(&op),
+            AtomKind::Atom(atom) if atom == &*types::Str => match op {
+                IROp::Add(_) => "__stradd__",
+                _ => unreachable!("only += is allowed on a str"),
+            },
+            _ => return Emit::Line(format!("{} {}= {}", target, c_op(&op), val)),
+        };
+        Emit::Line(self.call("__update__", vec![target, func.to_string(), val]))
+    }
+
+    fn bond_unary(&mut self, op: IROp) {
+        let ty = self.borrow().get_ty();
+        let operand = self.pop_str();
+        let item = if ty.kind == AtomKind::Dynamic {
+            let call = match op {
+                IROp::Neg(_) => "__neg__",
+                IROp::Not => "__not__",
+                _ => "__bitnot__",
+            };
+            Item::Expr(ty, self.call_one(call, operand))
+        } else {
+            let c_op = match op {
+                IROp::Neg(_) => "-",
+                IROp::Not => "!",
+                _ => "~",
+            };
+            narrow(Item::Expr(get_op_type(&op), format!("{c_op}({operand})")))
+        };
+        self.push(item);
+    }
+
     fn bond_conv(&mut self, into: AtomType, from: AtomType) {
         let item = self.pop_str();
         let conv = match &into.kind {
             &AtomKind::Dynamic => match from.kind {
-                AtomKind::Basic(BasicType::Int) => self.call_one("__int__", item),
+                AtomKind::Basic(BasicType::I64 | BasicType::U32) => self.call_one("__i64__", item),
+                AtomKind::Basic(BasicType::U64) => self.call_one("__u64__", item),
+                AtomKind::Basic(BasicType::F64) => self.call_one("__f64__", item),
                 AtomKind::Basic(BasicType::Float) => self.call_one("__float__", item),
                 AtomKind::Basic(BasicType::Bool) => self.call_one("__bool__", item),
+                AtomKind::Basic(BasicType::Char) => self.call_one("__char__", item),
+                // every smaller int fits in an int
+                AtomKind::Basic(_) => self.call_one("__int__", item),
 
                 AtomKind::Atom(ref atom) if atom == &*types::Str => self.call_one("__str__", item),
 
@@ -338,13 +813,35 @@ impl Codegen {
                 _ => todo!("add conv dynamic from {}", from),
             },
 
-            &AtomKind::Basic(BasicType::Float) => match from.kind {
-                AtomKind::Basic(BasicType::Int) => format!("(float){item}"),
-                _ => panic!(),
-            },
+            // a Dynamic holds the smaller ints as an int and a u32 as an i64
+            &AtomKind::Basic(basic) if from.kind == AtomKind::Dynamic => {
+                let held = match basic {
+                    BasicType::I8 | BasicType::I16 | BasicType::U8 | BasicType::U16 => {
+                        BasicType::Int
+                    }
+                    BasicType::U32 => BasicType::I64,
+                    basic => basic,
+                };
+                let (kind, field) = obj_kind(&AtomType {
+                    kind: AtomKind::Basic(held),
+                    details: None,
+                });
+                format!(
+                    "({})__unbox__({item}, {kind}).val.{field}",
+                    type_to_c(into.clone())
+                )
+            }
+            // a number widening into another
+            &AtomKind::Basic(_) => format!("({}){item}", type_to_c(into.clone())),
 
             &AtomKind::Atom(ref atom) if atom == &*types::Str => match from.kind {
-                AtomKind::Basic(BasicType::Int) => format!("itos({item})"),
+                AtomKind::Basic(BasicType::I64 | BasicType::U32) => format!("i64tos({item})"),
+                AtomKind::Basic(BasicType::U64) => format!("u64tos({item})"),
+                AtomKind::Basic(BasicType::Char) => format!("ctos({item})"),
+                AtomKind::Basic(BasicType::Bool) => format!("btos({item})"),
+                AtomKind::Basic(number) if number.is_float() => format!("ftos({item})"),
+                AtomKind::Dynamic => format!("otos({item})"),
+                AtomKind::Basic(number) if number.is_int() => format!("itos({item})"),
                 _ => panic!(),
             },
 
@@ -359,3 +856,119 @@ impl Codegen {
         self.push(Item::Expr(into, conv));
     }
 }
+
+// c cases have to be ints that do not overlap, and a break in one leaves the switch instead of the loop
+fn switchable(value_ty: &AtomType, arms: &[(Pattern, Vec<IROp>)]) -> bool {
+    let ints = match &value_ty.kind {
+        AtomKind::Basic(basic) => {
+            basic.is_int() || basic == &BasicType::Char || basic == &BasicType::Bool
+        }
+        AtomKind::Dynamic => true,
+        _ => false,
+    };
+
+    let ranges: Vec<(i128, i128)> = arms
+        .iter()
+        .filter_map(|(pattern, _)| pattern_range(pattern))
+        .collect();
+    let overlap = ranges
+        .iter()
+        .enumerate()
+        .any(|(i, a)| ranges[i + 1..].iter().any(|b| a.0 < b.1 && b.0 < a.1));
+
+    ints && !overlap && !arms.iter().any(|(_, body)| breaks(body))
+}
+
+// a break without a label in these ops, one in a loop of their own leaves that loop
+fn breaks(ops: &[IROp]) -> bool {
+    ops.iter().any(|op| match op {
+        IROp::Break(None) => true,
+        IROp::If(_, body, alt) => breaks(body) || breaks(alt),
+        IROp::Block(_, body) => breaks(body),
+        IROp::Match(_, _, arms) => arms.iter().any(|(_, body)| breaks(body)),
+        _ => false,
+    })
+}
+
+// the literal right before the end of a range, c case ranges include their end
+fn before(end: &Literal) -> Literal {
+    match end {
+        &Literal::Int(i, ty) => Literal::Int(i - 1, ty),
+        &Literal::Char(c) => Literal::Char(c - 1),
+        literal => literal.clone(),
+    }
+}
+
+// C does arithmetic on numbers smaller than an int as ints, casting the result back wraps it like the number would
+fn narrow(item: Item) -> Item {
+    match item {
+        Item::Expr(ty, expr) if matches!(ty.kind, AtomKind::Basic(number) if number.is_int() && number.bits() < 32) =>
+        {
+            let expr = format!("(({}){})", type_to_c(ty.clone()), expr);
+            Item::Expr(ty, expr)
+        }
+        item => item,
+    }
+}
+
+// the kind of a Dynamic holding the type and the field of its Value
+fn obj_kind(ty: &AtomType) -> (&'static str, &'static str) {
+    match &ty.kind {
+        AtomKind::Basic(BasicType::Int) => ("INT_TYPE", "i"),
+        AtomKind::Basic(BasicType::I64) => ("I64_TYPE", "l"),
+        AtomKind::Basic(BasicType::U64) => ("U64_TYPE", "u"),
+        AtomKind::Basic(BasicType::Float) => ("FLOAT_TYPE", "f"),
+        AtomKind::Basic(BasicType::F64) => ("F64_TYPE", "d"),
+        AtomKind::Basic(BasicType::Char) => ("CHAR_TYPE", "c"),
+        AtomKind::Basic(BasicType::Bool) => ("BOOL_TYPE", "b"),
+        _ => ("STR_TYPE", "s"),
+    }
+}
+
+fn dynamic_op(op: &IROp) -> &'static str {
+    match op {
+        IROp::Add(_) => "__add__",
+        IROp::Sub(_) => "__sub__",
+        IROp::Mul(_) => "__mul__",
+        IROp::Div(_) => "__div__",
+        IROp::Mod(_) => "__mod__",
+        IROp::BitAnd(_) => "__band__",
+        IROp::BitOr(_) => "__bor__",
+        IROp::BitXor(_) => "__bxor__",
+        IROp::Shl(_) => "__shl__",
+        IROp::Shr(_) => "__shr__",
+        IROp::Lt => "__lt__",
+        IROp::Le => "__le__",
+        IROp::Gt => "__gt__",
+        IROp::Ge => "__ge__",
+        IROp::Eq => "__eq__",
+        IROp::NotEq => "__neq__",
+        IROp::And => "__and__",
+        IROp::Or => "__or__",
+        _ => todo!(),
+    }
+}
+
+fn c_op(op: &IROp) -> &'static str {
+    match op {
+        IROp::Add(_) => "+",
+        IROp::Sub(_) => "-",
+        IROp::Mul(_) => "*",
+        IROp::Div(_) => "/",
+        IROp::Mod(_) => "%",
+        IROp::BitAnd(_) => "&",
+        IROp::BitOr(_) => "|",
+        IROp::BitXor(_) => "^",
+        IROp::Shl(_) => "<<",
+        IROp::Shr(_) => ">>",
+        IROp::Lt => "<",
+        IROp::Le => "<=",
+        IROp::Gt => ">",
+        IROp::Ge => ">=",
+        IROp::Eq => "==",
+        IROp::NotEq => "!=",
+        IROp::And => "&&",
+        IROp::Or => "||",
+        _ => todo!("unimplented op {:#?}", op),
+    }
+}
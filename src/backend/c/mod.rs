@@ -39,14 +39,50 @@ pub fn compile(config: &CompilerConfig, ir: Vec<IROp>) {
         .wait();
 }
 
+// C names cannot contain ::, paths into mods are joined with $ instead
+// an id never has a $ so they cannot clash with a name outside of a mod
+pub fn c_name(name: &str) -> String {
+    name.replace("::", "$")
+}
+
+// a C string literal holding s, bytes outside of printable ascii are written as octal escapes
+pub fn c_str(s: &str) -> String {
+    let mut res = String::from("\"");
+    for byte in s.bytes() {
+        match byte {
+            b'"' => res.push_str("\\\""),
+            b'\\' => res.push_str("\\\\"),
+            // ?? starts a trigraph
+            b'?' => res.push_str("\\?"),
+            b'\n' => res.push_str("\\n"),
+            b'\t' => res.push_str("\\t"),
+            b' '..=b'~' => res.push(byte as char),
+            _ => res.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    res.push('"');
+    res
+}
+
 pub fn type_to_c(ty: AtomType) -> String {
     match ty.kind {
         AtomKind::Basic(BasicType::Int) => "int",
+        AtomKind::Basic(BasicType::I8) => "int8_t",
+        AtomKind::Basic(BasicType::I16) => "int16_t",
+        AtomKind::Basic(BasicType::I64) => "int64_t",
+        AtomKind::Basic(BasicType::U8) => "uint8_t",
+        AtomKind::Basic(BasicType::U16) => "uint16_t",
+        AtomKind::Basic(BasicType::U32) => "uint32_t",
+        AtomKind::Basic(BasicType::U64) => "uint64_t",
         AtomKind::Basic(BasicType::Float) => "float",
+        AtomKind::Basic(BasicType::F64) => "double",
+        AtomKind::Basic(BasicType::Char) => "char",
         AtomKind::Basic(BasicType::Bool) => "_Bool",
         AtomKind::Basic(BasicType::Void) => "void",
 
         AtomKind::Dynamic => "Obj",
+        // a function value, see std.h
+        AtomKind::Function(_) => "Closure*",
 
         AtomKind::Atom(ref atom) if atom == &*types::Str => "Str*",
         AtomKind::Atom(ref atom) if &atom.name == &*types::List.name => "List*",
@@ -65,6 +101,10 @@ pub fn type_to_c(ty: AtomType) -> String {
             return format!("const {}", type_to_c(T.clone()));
         }
 
+        // bonds are passed by value, atoms are heap allocated
+        AtomKind::Atom(ref atom) if atom.bond => return c_name(&atom.name),
+        AtomKind::Atom(ref atom) => return format!("{}*", c_name(&atom.name)),
+
         _ => todo!("{:?}", ty),
     }
     .to_string()
@@ -175,6 +215,8 @@ impl Emiter {
 #[derive(Debug, Clone)]
 pub struct Module {
     includes: Vec<String>,
+    typedefs: Vec<String>,
+    structs: Vec<String>,
     externs: Vec<String>,
     functions: Vec<Vec<String>>,
     pub col: RefCell<u32>,
@@ -195,6 +237,8 @@ impl Module {
     pub fn new() -> Self {
         Self {
             includes: Vec::new(),
+            typedefs: Vec::new(),
+            structs: Vec::new(),
             externs: Vec::new(),
             functions: Vec::new(),
             col: RefCell::new(0),
@@ -207,6 +251,19 @@ impl Module {
         }
     }
 
+    // structs are typedefed before any is defined so they can refer to each other
+    pub fn struct_add(&mut self, name: String, fields: Vec<(AtomType, String)>) {
+        let name = c_name(&name);
+        self.typedefs.push(format!("typedef struct {name} {name};"));
+
+        let mut lines = vec![format!("struct {name} {{")];
+        for (ty, field) in fields {
+            lines.push(format!("\t{} {field};", type_to_c(ty)));
+        }
+        lines.push("};".to_string());
+        self.structs.push(lines.join("\n"));
+    }
+
     pub fn extern_add(&mut self, extern_: String) {
         if !self.externs.contains(&extern_) {
             self.externs.push(extern_);
@@ -223,6 +280,8 @@ impl Module {
         let mut lines = Vec::new();
         lines.append(&mut self.includes);
 
+        lines.append(&mut self.typedefs);
+        lines.append(&mut self.structs);
         lines.append(&mut self.externs);
 
         lines.append(&mut func_lines);
@@ -245,6 +304,9 @@ pub struct Codegen {
     stack: Vec<Item>,
     variables: HashMap<String, (i32, AtomType)>, // c doesnt allow redeclaration of vars with different types
     pub module: Module,                          // code we are generating
+    labels: Vec<(String, String)>, // named loops around the code being generated and their c labels
+    label_count: u32,              // c labels have to be unique in a function
+    temp_count: u32, // numbers the temporaries holding the value of an if or a block and the functions of lambdas
 }
 
 impl Codegen {
@@ -262,9 +324,19 @@ impl Codegen {
         let item = self.pop();
         match item {
             Item::Const(con) => match con {
-                Literal::Int(i) => i.to_string(),
-                Literal::Float(f) => f.to_string(),
-                Literal::Str(s) => format!("__strnew__(\"{}\")", s),
+                // the C literal would be too big before it is negated
+                Literal::Int(i, BasicType::I64) if i == i64::MIN as i128 => {
+                    format!("({}LL - 1)", i + 1)
+                }
+                Literal::Int(i, BasicType::I64) => format!("{i}LL"),
+                Literal::Int(i, BasicType::U64) => format!("{i}ULL"),
+                Literal::Int(i, BasicType::U32) => format!("{i}U"),
+                Literal::Int(i, _) => i.to_string(),
+                // always written with a point or an exponent, 1 would be an int in C
+                Literal::Float(f, BasicType::Float) => format!("{:?}f", f as f32),
+                Literal::Float(f, _) => format!("{:?}", f),
+                Literal::Char(c) => format!("(char){c}"),
+                Literal::Str(s) => format!("__strnew__({}, {})", c_str(&s), s.len()),
                 Literal::Bool(b) => (b as u8).to_string(),
             },
             Item::Var(_, name) => name,
@@ -273,12 +345,19 @@ impl Codegen {
                 let mut items = self.pop_amount(size);
                 items.reverse();
                 let new = items.join(", ");
-                format!(
-                    "__listnew__({}, {}, {})",
-                    format!("sizeof({})", type_to_c(ty)),
-                    size,
-                    new
-                )
+                // the list is a List(T), each item is a T
+                let elem = match ty.kind {
+                    AtomKind::Atom(ref atom) => atom.generics[0].clone(),
+                    _ => ty,
+                };
+                let elem = type_to_c(elem);
+                // the items are copied out of an array of them, an empty list has none
+                let array = if size == 0 {
+                    "NULL".to_string()
+                } else {
+                    format!("({elem}[]){{{new}}}")
+                };
+                format!("__listnew__({array}, sizeof({elem}), {size})")
             }
         }
     }
@@ -299,13 +378,16 @@ impl Codegen {
             stack: Vec::new(),
             variables: HashMap::new(),
             module: Module::new(),
+            labels: Vec::new(),
+            label_count: 0,
+            temp_count: 0,
         }
     }
 
     pub fn get_var(&mut self, name: String) -> String {
         let count = self.variables.get(&name);
         if count.is_none() {
-            return name;
+            return c_name(&name);
         }
         let count = count.unwrap().0;
 
@@ -1,5 +1,6 @@
 #include "std.h"
 #include "stdio.h"
+#include <inttypes.h>
 #include <stdarg.h>
 #include <stdbool.h>
 #include <stdlib.h>
@@ -19,6 +20,12 @@ void GC_init();
       return __int__(a.val.i op b.val.i);                                      \
     case FLOAT_TYPE:                                                           \
       return __float__(a.val.f op b.val.f);                                    \
+    case I64_TYPE:                                                             \
+      return __i64__(a.val.l op b.val.l);                                      \
+    case U64_TYPE:                                                             \
+      return __u64__(a.val.u op b.val.u);                                      \
+    case F64_TYPE:                                                             \
+      return __f64__(a.val.d op b.val.d);                                      \
     default:                                                                   \
       return __NaN__();                                                        \
     }
@@ -29,6 +36,10 @@ void GC_init();
     switch (kind) {                                                            \
     case INT_TYPE:                                                             \
       return __int__(a.val.i op b.val.i);                                      \
+    case I64_TYPE:                                                             \
+      return __i64__(a.val.l op b.val.l);                                      \
+    case U64_TYPE:                                                             \
+      return __u64__(a.val.u op b.val.u);                                      \
     default:                                                                   \
       return __NaN__();                                                        \
     }
@@ -55,6 +66,14 @@ void GC_init();
       return a.val.f op b.val.f;                                               \
     case BOOL_TYPE:                                                            \
       return a.val.b op b.val.b;                                               \
+    case I64_TYPE:                                                             \
+      return a.val.l op b.val.l;                                               \
+    case U64_TYPE:                                                             \
+      return a.val.u op b.val.u;                                               \
+    case F64_TYPE:                                                             \
+      return a.val.d op b.val.d;                                               \
+    case CHAR_TYPE:                                                            \
+      return a.val.c op b.val.c;                                               \
     case STR_TYPE:                                                             \
       return __str##name##__(a.val.s, b.val.s);                                \
     default:                                                                   \
@@ -69,6 +88,12 @@ void GC_init();
       return __int__(a.val.i op b.val.i);                                      \
     case FLOAT_TYPE:                                                           \
       return __float__(a.val.f op b.val.f);                                    \
+    case I64_TYPE:                                                             \
+      return __i64__(a.val.l op b.val.l);                                      \
+    case U64_TYPE:                                                             \
+      return __u64__(a.val.u op b.val.u);                                      \
+    case F64_TYPE:                                                             \
+      return __f64__(a.val.d op b.val.d);                                      \
     case STR_TYPE:                                                             \
       return __str__(__str##name##__(a.val.s, b.val.s));                       \
     default:                                                                   \
@@ -84,23 +109,95 @@ Obj __NaN__() {
   return nan;
 }
 
-void __conv__(Obj *a, Obj *b) {
-  TYPE a_ty = (a)->kind;
-  TYPE b_ty = (b)->kind;
+Obj __neg__(Obj a) {
+  switch (a.kind) {
+  case INT_TYPE:
+    return __int__(-a.val.i);
+  case FLOAT_TYPE:
+    return __float__(-a.val.f);
+  case I64_TYPE:
+    return __i64__(-a.val.l);
+  case F64_TYPE:
+    return __f64__(-a.val.d);
+  default:
+    return __NaN__();
+  }
+}
+
+Obj __not__(Obj a) {
+  if (a.kind != BOOL_TYPE) {
+    return __NaN__();
+  }
+  return __bool__(!a.val.b);
+}
+
+Obj __bitnot__(Obj a) {
+  switch (a.kind) {
+  case INT_TYPE:
+    return __int__(~a.val.i);
+  case I64_TYPE:
+    return __i64__(~a.val.l);
+  case U64_TYPE:
+    return __u64__(~a.val.u);
+  default:
+    return __NaN__();
+  }
+}
+
+// widens a number into a wider kind, see types::implicit_conversions
+Obj __widen__(Obj a, TYPE into) {
+  switch (into) {
+  case FLOAT_TYPE:
+    if (a.kind == INT_TYPE)
+      return __float__((float)a.val.i);
+    break;
+  case I64_TYPE:
+    if (a.kind == INT_TYPE)
+      return __i64__(a.val.i);
+    break;
+  case F64_TYPE:
+    switch (a.kind) {
+    case INT_TYPE:
+      return __f64__(a.val.i);
+    case FLOAT_TYPE:
+      return __f64__(a.val.f);
+    case I64_TYPE:
+      return __f64__(a.val.l);
+    case U64_TYPE:
+      return __f64__(a.val.u);
+    }
+    break;
+  }
+  return __NaN__();
+}
 
-  Value a_val = a->val;
-  Value b_val = b->val;
+void __conv__(Obj *a, Obj *b) {
+  if (a->kind == b->kind) {
+    return;
+  }
 
-  if (a_ty == b_ty) {
+  Obj wide = __widen__(*b, a->kind);
+  if (wide.kind == a->kind) {
+    *b = wide;
     return;
   }
-  if (a_ty == FLOAT_TYPE && b_ty == INT_TYPE) {
-    *b = __float__((float)b_val.i);
-  } else if (a_ty == INT_TYPE && b_ty == FLOAT_TYPE) {
-    *a = __float__((float)a_val.i);
-  } else {
-    err("cannot conv balance a and b", 5);
+  wide = __widen__(*a, b->kind);
+  if (wide.kind == b->kind) {
+    *a = wide;
+    return;
   }
+  err("cannot conv balance a and b", 5);
+}
+
+// the Obj a Dynamic converted into a type of kind holds, a narrower number is widened first
+Obj __unbox__(Obj obj, TYPE kind) {
+  if (obj.kind != kind) {
+    obj = __widen__(obj, kind);
+  }
+  if (obj.kind != kind) {
+    err("a Dynamic does not hold the type it is converted into\n", 5);
+  }
+  return obj;
 }
 
 void writeln(Obj arg) {
@@ -115,8 +212,25 @@ void writeln(Obj arg) {
     printf("%f\n", arg.val.f);
     break;
   }
+  case I64_TYPE: {
+    printf("%" PRId64 "\n", arg.val.l);
+    break;
+  }
+  case U64_TYPE: {
+    printf("%" PRIu64 "\n", arg.val.u);
+    break;
+  }
+  case F64_TYPE: {
+    printf("%f\n", arg.val.d);
+    break;
+  }
+  case CHAR_TYPE: {
+    printf("%c\n", arg.val.c);
+    break;
+  }
   case STR_TYPE: {
-    printf("%.*s\n", arg.val.s->len, arg.val.s->val);
+    fwrite(arg.val.s->val, 1, arg.val.s->size, stdout);
+    printf("\n");
     break;
   }
   case BOOL_TYPE: {
@@ -129,15 +243,15 @@ void writeln(Obj arg) {
   }
 }
 
-Str *__strnew__(char *s) {
-  int len = strlen(s);
+// len is given so a str can hold a \0
+Str *__strnew__(char *s, int len) {
   char *str = (char *)GC_malloc(len);
   memcpy(str, s, len);
 
   Str *obj = (Str *)GC_malloc(sizeof(Str));
 
   obj->val = str;
-  obj->len = len;
+  obj->size = len;
   return obj;
 }
 
@@ -149,24 +263,31 @@ DEF(N, mul, *);
 
 DEF(N, div, /);
 DEF(NF, mod, %);
+DEF(NF, band, &);
+DEF(NF, bor, |);
+DEF(NF, bxor, ^);
+DEF(NF, shl, <<);
+DEF(NF, shr, >>);
 
 DEF(LOGICAL, and, &&);
 DEF(LOGICAL, or, ||);
 
 DEF(BOOL, eq, ==);
+DEF(BOOL, neq, !=);
 
-DEF(BOOL, comp, >);
-
-DEF(BOOL, ecomp, >=);
+DEF(BOOL, lt, <);
+DEF(BOOL, le, <=);
+DEF(BOOL, gt, >);
+DEF(BOOL, ge, >=);
 
 _Bool __streq__(Str *a, Str *b) {
-  if (a->len != b->len) {
+  if (a->size != b->size) {
     return false;
   }
 
   char *str_a = a->val;
   char *str_b = b->val;
-  for (int i = 0; i < a->len; i++) {
+  for (int i = 0; i < a->size; i++) {
     if (str_a[i] != str_b[i]) {
       return false;
     }
@@ -174,30 +295,33 @@ _Bool __streq__(Str *a, Str *b) {
   return true;
 }
 
-_Bool __strcomp__(Str *a, Str *b) {
-  int len_a = a->len;
-  int len_b = b->len;
-  return len_a > len_b;
-}
+_Bool __strneq__(Str *a, Str *b) { return !__streq__(a, b); }
 
-_Bool __strecomp__(Str *a, Str *b) {
-  int len_a = a->len;
-  int len_b = b->len;
-  char *str_a = a->val;
-  char *str_b = b->val;
-  return len_a > len_b || str_a == str_b;
+// strs compare by their bytes, a str that is the start of another comes first
+int __strcmp__(Str *a, Str *b) {
+  size_t len = a->size < b->size ? a->size : b->size;
+  int cmp = memcmp(a->val, b->val, len);
+  if (cmp != 0) {
+    return cmp;
+  }
+  return (a->size > b->size) - (a->size < b->size);
 }
 
+_Bool __strlt__(Str *a, Str *b) { return __strcmp__(a, b) < 0; }
+_Bool __strle__(Str *a, Str *b) { return __strcmp__(a, b) <= 0; }
+_Bool __strgt__(Str *a, Str *b) { return __strcmp__(a, b) > 0; }
+_Bool __strge__(Str *a, Str *b) { return __strcmp__(a, b) >= 0; }
+
 Str *__stradd__(Str *a, Str *b) {
-  int len = a->len + b->len;
+  int len = a->size + b->size;
   char *str = (char *)GC_malloc(len);
-  memcpy(str, a->val, a->len);
-  memcpy(str + a->len, b->val, b->len);
+  memcpy(str, a->val, a->size);
+  memcpy(str + a->size, b->val, b->size);
 
   Str *s = (Str *)GC_malloc(sizeof(Str));
 
   s->val = str;
-  s->len = len;
+  s->size = len;
   return s;
 }
 
@@ -216,6 +340,14 @@ unsigned int type_size(TYPE ty) {
     return STR_SIZE;
   case BOOL_TYPE:
     return BOOL_SIZE;
+  case I64_TYPE:
+    return I64_SIZE;
+  case U64_TYPE:
+    return U64_SIZE;
+  case F64_TYPE:
+    return F64_SIZE;
+  case CHAR_TYPE:
+    return CHAR_SIZE;
   default:
     err("UNKNOWN TYPE", 3);
     return sizeof(Obj);
@@ -223,7 +355,7 @@ unsigned int type_size(TYPE ty) {
 }
 
 Str *__strclone__(Str *obj) {
-  unsigned int size = STR_SIZE + (1 * obj->len);
+  unsigned int size = STR_SIZE + (1 * obj->size);
   Str *cloned = GC_malloc(size);
   memcpy(cloned, obj, size);
   return cloned;
@@ -231,15 +363,10 @@ Str *__strclone__(Str *obj) {
 
 void __init__() { GC_init(); }
 
-List *__listnew__(size_t elem_size, size_t size, ...) {
+// items is an array of size items, each elem_size bytes
+List *__listnew__(void *items, size_t elem_size, size_t size) {
   void *arr = GC_malloc(elem_size * size);
-  va_list args;
-  va_start(args, size);
-
-  char *next_ele = (char *)arr;
-  for (int i = 0; i < size * elem_size / 4; i++) {
-    ((int *)arr)[i] = va_arg(args, int);
-  }
+  memcpy(arr, items, elem_size * size);
 
   List *list = (List *)GC_malloc(sizeof(List));
   list->array = arr;
@@ -255,14 +382,19 @@ List *Listdotpush(List *self, Obj item) {
       GC_realloc(self->array, self->size * self->elem_size + self->elem_size);
   void *insert = (char *)self->array + self->size * self->elem_size;
   memcpy(insert, &item.val, self->elem_size);
+  self->size++;
 
   return self;
 }
 
 // set pop(T): List(T) self -> List(T)
 List *Listdotpop(List *self) {
-  self->array =
-      GC_realloc(self->array, self->size * self->elem_size - self->elem_size);
+  if (self->size == 0) {
+    return self;
+  }
+
+  self->size--;
+  self->array = GC_realloc(self->array, self->size * self->elem_size);
   return self;
 }
 
@@ -293,21 +425,93 @@ IonizedInt *IonizedIntdotNew(int val) {
 
 // testing externs
 Str *cprompt(Str *msg) {
-  printf("%.*s", msg->len, msg->val);
+  printf("%.*s", msg->size, msg->val);
   char *cstr;
   scanf("%s", cstr);
-  return __strnew__(cstr);
+  return __strnew__(cstr, strlen(cstr));
 }
 
 Str *itos(int i) {
   char *str = GC_malloc(10);
   sprintf(str, "%d", i);
-  return __strnew__(str);
+  return __strnew__(str, strlen(str));
+}
+
+Str *i64tos(int64_t i) {
+  char *str = GC_malloc(21);
+  sprintf(str, "%" PRId64, i);
+  return __strnew__(str, strlen(str));
+}
+
+Str *u64tos(uint64_t u) {
+  char *str = GC_malloc(21);
+  sprintf(str, "%" PRIu64, u);
+  return __strnew__(str, strlen(str));
+}
+
+Str *ctos(char c) {
+  char *str = GC_malloc(2);
+  str[0] = c;
+  str[1] = '\0';
+  return __strnew__(str, 1);
+}
+
+Str *btos(_Bool b) {
+  return b ? __strnew__("true", 4) : __strnew__("false", 5);
+}
+
+Str *ftos(double f) {
+  char *str = GC_malloc(32);
+  snprintf(str, 32, "%f", f);
+  return __strnew__(str, strlen(str));
+}
+
+// the text writeln shows for obj
+Str *otos(Obj obj) {
+  switch (obj.kind) {
+  case INT_TYPE:
+    return itos(obj.val.i);
+  case FLOAT_TYPE:
+    return ftos(obj.val.f);
+  case STR_TYPE:
+    return obj.val.s;
+  case BOOL_TYPE:
+    return btos(obj.val.b);
+  case I64_TYPE:
+    return i64tos(obj.val.l);
+  case U64_TYPE:
+    return u64tos(obj.val.u);
+  case F64_TYPE:
+    return ftos(obj.val.d);
+  case CHAR_TYPE:
+    return ctos(obj.val.c);
+  default:
+    err("cannot turn an unknown type into a str", 3);
+    return NULL;
+  }
+}
+
+char __strget__(Str *str, int index) {
+  if (index < 0 || index >= str->size) {
+    char msg[64];
+    sprintf(msg, "index %d out of bounds for str of size %d\n", index, str->size);
+    err(msg, 6);
+  }
+  return str->val[index];
+}
+
+char chr(int code) { return (char)code; }
+
+Closure *__closure__(void *fn, void *env) {
+  Closure *closure = GC_malloc(sizeof(Closure));
+  closure->fn = fn;
+  closure->env = env;
+  return closure;
 }
 
 char *strtocstr(Str *str) {
-  char *cstr = GC_malloc(str->len + 1);
-  strcpy(cstr, str->val);
-  cstr[str->len] = '\0';
+  char *cstr = GC_malloc(str->size + 1);
+  memcpy(cstr, str->val, str->size);
+  cstr[str->size] = '\0';
   return cstr;
 }
\ No newline at end of file
@@ -1,18 +1,27 @@
 #include "stdbool.h"
+#include "stdint.h"
 #include "stdlib.h"
 
 void GC_free(void *);
+void *GC_malloc(unsigned int);
 #define free(a) GC_free(a) // remove later
 
+// allocates an atom
+#define __new__(type) ((type *)GC_malloc(sizeof(type)))
+
 #define INT_TYPE 0
 #define FLOAT_TYPE 1
 #define STR_TYPE 2
 #define BOOL_TYPE 3
+#define I64_TYPE 4
+#define U64_TYPE 5
+#define F64_TYPE 6
+#define CHAR_TYPE 7
 typedef char TYPE;
 
 typedef struct Str {
   char *val;
-  int len;
+  int size;
 } Str;
 
 typedef union Value {
@@ -20,12 +29,20 @@ typedef union Value {
   float f;
   _Bool b;
   Str *s;
+  int64_t l;
+  uint64_t u;
+  double d;
+  char c;
 } Value;
 
 #define INT_SIZE sizeof(TYPE) + sizeof(int)
 #define FLOAT_SIZE sizeof(TYPE) + sizeof(float)
 #define STR_SIZE sizeof(TYPE) + sizeof(Str)
 #define BOOL_SIZE sizeof(TYPE) + sizeof(_Bool)
+#define I64_SIZE sizeof(TYPE) + sizeof(int64_t)
+#define U64_SIZE sizeof(TYPE) + sizeof(uint64_t)
+#define F64_SIZE sizeof(TYPE) + sizeof(double)
+#define CHAR_SIZE sizeof(TYPE) + sizeof(char)
 
 typedef struct {
   TYPE kind;
@@ -57,27 +74,57 @@ void writeln(Obj arg);
 #define __float__(flo) ((Obj){FLOAT_TYPE, (Value){.f = flo}})
 #define __str__(str) ((Obj){STR_TYPE, (Value){.s = str}})
 #define __bool__(bool) ((Obj){BOOL_TYPE, (Value){.b = bool}})
+#define __i64__(i64) ((Obj){I64_TYPE, (Value){.l = i64}})
+#define __u64__(u64) ((Obj){U64_TYPE, (Value){.u = u64}})
+#define __f64__(f64) ((Obj){F64_TYPE, (Value){.d = f64}})
+#define __char__(chr) ((Obj){CHAR_TYPE, (Value){.c = chr}})
+
+// exits if obj cannot be turned into kind
+Obj __unbox__(Obj obj, TYPE kind);
 
-Str *__strnew__(char *val);
+Str *__strnew__(char *val, int len);
 
 void err(char *err, int code);
 Obj __add__(Obj a, Obj b);
 Obj __sub__(Obj a, Obj b);
 Obj __mul__(Obj a, Obj b);
 Obj __div__(Obj a, Obj b);
-
-_Bool __comp__(Obj a, Obj b);
-_Bool __ecomp__(Obj a, Obj b);
+Obj __mod__(Obj a, Obj b);
+Obj __band__(Obj a, Obj b);
+Obj __bor__(Obj a, Obj b);
+Obj __bxor__(Obj a, Obj b);
+Obj __shl__(Obj a, Obj b);
+Obj __shr__(Obj a, Obj b);
+
+Obj __neg__(Obj a);
+Obj __not__(Obj a);
+Obj __bitnot__(Obj a);
+
+_Bool __lt__(Obj a, Obj b);
+_Bool __le__(Obj a, Obj b);
+_Bool __gt__(Obj a, Obj b);
+_Bool __ge__(Obj a, Obj b);
 _Bool __eq__(Obj a, Obj b);
+_Bool __neq__(Obj a, Obj b);
 
-_Bool __strcomp__(Str *a, Str *b);
-_Bool __strecomp__(Str *a, Str *b);
+_Bool __strlt__(Str *a, Str *b);
+_Bool __strle__(Str *a, Str *b);
+_Bool __strgt__(Str *a, Str *b);
+_Bool __strge__(Str *a, Str *b);
 _Bool __streq__(Str *a, Str *b);
+_Bool __strneq__(Str *a, Str *b);
 
 Str *__stradd__(Str *a, Str *b);
 Str *__strclone__(Str *obj);
 void __init__();
 
+// target = op(target, val) with target evaluated once, for ops that are calls
+#define __update__(target, op, val)                                            \
+  ({                                                                           \
+    __typeof__(target) *__target__ = &(target);                                \
+    *__target__ = op(*__target__, val);                                        \
+  })
+
 #define __listget__(list, type, index) (((type *)(list)->array)[index])
 
 typedef struct List {
@@ -87,9 +134,27 @@ typedef struct List {
 } List;
 
 List *Listdotpush(List *self, Obj item);
-List *__listnew__(size_t elem_size, size_t size, ...);
+List *__listnew__(void *items, size_t elem_size, size_t size);
 List *Listdotpop(List *self);
 
 void __free__(void *item);
 
-Str *itos(int i);
\ No newline at end of file
+Str *itos(int i);
+Str *i64tos(int64_t i);
+Str *u64tos(uint64_t u);
+Str *ctos(char c);
+Str *btos(_Bool b);
+Str *ftos(double f);
+Str *otos(Obj obj);
+
+// a function value, fn is called with env as its first argument
+typedef struct Closure {
+  void *fn;
+  void *env;
+} Closure;
+
+Closure *__closure__(void *fn, void *env);
+
+// reads the char at index, exits if it is out of bounds
+char __strget__(Str *str, int index);
+char chr(int code);
\ No newline at end of file
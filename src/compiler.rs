@@ -2,6 +2,7 @@ use std::env::current_exe;
 
 use crate::analysis::Analyzer;
 use crate::backend::c;
+use crate::err::ATErr;
 use crate::ir::gen::IRGen;
 use crate::ir::Codegen;
 use crate::parser::parse::Parse;
@@ -26,6 +27,7 @@ pub enum Backend {
 }
 pub struct CompilerConfig {
     input: String,
+    pub file: String, // path of the input, empty when it doesnt come from a file
     pub libdir: String,
     pub backend: Backend,
     pub debug: bool,
@@ -35,6 +37,7 @@ pub struct CompilerConfig {
 impl CompilerConfig {
     pub fn new(
         input: String,
+        file: String,
         backend: Backend,
         debug: bool,
         output: String,
@@ -42,6 +45,7 @@ impl CompilerConfig {
     ) -> Self {
         Self {
             input,
+            file,
             libdir: format!(
                 "{}/lib",
                 current_exe().unwrap().parent().unwrap().to_str().unwrap()
@@ -52,11 +56,25 @@ impl CompilerConfig {
             workdir,
         }
     }
-    pub fn compile(&self) {
-        let mut parser = Parser::new(self.input.clone());
+    // compiles the input, returns every error found if it fails
+    pub fn compile(&self) -> Result<(), Vec<ATErr>> {
+        let mut parser = Parser::new(self.input.clone(), self.file.clone());
         let prog = parser.parse_prog();
 
-        let prog = Analyzer::analyz_prog(prog, parser.functions, self.workdir.clone()).unwrap();
+        // syntax errors are reported before analyzing so they dont cascade into type errors
+        let errors = parser.errors();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let prog = Analyzer::analyz_prog(
+            prog,
+            parser.functions,
+            parser.atoms,
+            parser.mods,
+            self.workdir.clone(),
+            self.file.clone(),
+        )?;
         if self.debug {
             dbg!(&prog);
         }
@@ -72,5 +90,6 @@ impl CompilerConfig {
             }
             _ => todo!(),
         }
+        Ok(())
     }
 }
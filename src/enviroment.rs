@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::lexer::token::Span;
 use crate::parser::ast::{Blueprint, Literal};
 use crate::types::{self, AtomDetails, AtomKind, AtomType, BasicType, FunctionType};
 
@@ -11,6 +12,8 @@ pub struct Symbol {
     pub value: Option<Literal>,
 
     pub expected: Option<AtomType>,
+    pub mutable: bool, // parts cannot be assigned after they are declared
+    pub span: Span,    // where the symbol was declared
 }
 
 #[derive(Clone, Debug)]
@@ -34,6 +37,8 @@ impl Enviroment {
                         ty: $type,
                         value: None,
                         expected: None,
+                        mutable: false,
+                        span: Span::default(),
                     },
                 );
             };
@@ -65,8 +70,20 @@ impl Enviroment {
         }
 
         // default built-in types
-        ty!(AtomKind::Basic(BasicType::Int));
-        ty!(AtomKind::Basic(BasicType::Float));
+        for number in BasicType::NUMBERS {
+            ty!(AtomKind::Basic(number));
+        }
+        // int and float are i32 and f32
+        for number in [BasicType::Int, BasicType::Float] {
+            insert!(
+                &number.suffix(),
+                AtomType {
+                    kind: AtomKind::Basic(number),
+                    details: Some(AtomDetails::Type)
+                }
+            );
+        }
+        ty!(AtomKind::Basic(BasicType::Char));
         ty!(AtomKind::Basic(BasicType::Void));
         ty!(AtomKind::Dynamic);
         ty!(AtomKind::Basic(BasicType::Bool));
@@ -77,6 +94,38 @@ impl Enviroment {
         complex!(types::Str);
         complex!(types::Const);
 
+        // runtime functions extending built-in types, see std.c
+        macro_rules! func {
+            ($name: expr, [$($param: expr),*], $ret: expr) => {
+                insert!(
+                    $name,
+                    AtomType {
+                        kind: AtomKind::Function(FunctionType {
+                            params: vec![$(AtomType { kind: $param, details: None }),*],
+                            return_type: Box::new(AtomType { kind: $ret, details: None }),
+                        }),
+                        details: None
+                    }
+                );
+            };
+        }
+
+        func!(
+            "chr",
+            [AtomKind::Basic(BasicType::Int)],
+            AtomKind::Basic(BasicType::Char)
+        );
+        func!(
+            "Listdotpush",
+            [AtomKind::Atom(types::List.clone()), AtomKind::Dynamic],
+            AtomKind::Atom(types::List.clone())
+        );
+        func!(
+            "Listdotpop",
+            [AtomKind::Atom(types::List.clone())],
+            AtomKind::Atom(types::List.clone())
+        );
+
         Self {
             symbols,
             parent: None,
@@ -126,6 +175,45 @@ impl Enviroment {
         }
     }
 
+    // the name an id refers to from inside module
+    // locals come first then the closest mod declaring it then everything else
+    pub fn resolve(&self, name: &str, module: &str) -> String {
+        if self.is_local(name) {
+            return name.to_string();
+        }
+
+        let mut module = module;
+        while !module.is_empty() {
+            let qualified = format!("{}::{}", module, name);
+            if self.has(&qualified) {
+                return qualified;
+            }
+            module = module.rsplit_once("::").map_or("", |(parent, _)| parent);
+        }
+        name.to_string()
+    }
+
+    // declared in a child enviroment, not at the top level
+    pub fn is_local(&self, name: &str) -> bool {
+        match &self.parent {
+            Some(parent) => self.symbols.contains_key(name) || parent.is_local(name),
+            None => false,
+        }
+    }
+
+    // how many enviroments this one is nested in, the top level one is 0
+    pub fn depth(&self) -> usize {
+        self.parent.as_ref().map_or(0, |parent| parent.depth() + 1)
+    }
+
+    // depth of the closest enviroment declaring name
+    pub fn depth_of(&self, name: &str) -> Option<usize> {
+        if self.symbols.contains_key(name) {
+            return Some(self.depth());
+        }
+        self.parent.as_ref()?.depth_of(name)
+    }
+
     pub fn has(&self, name: &String) -> bool {
         if self.symbols.contains_key(name) {
             true
@@ -136,22 +224,6 @@ impl Enviroment {
         }
     }
 
-    //TODO: REMOVE
-    // member expr parent is passed to a function as first arg if it takes it as an arg for ex.
-    // set push: List(T) self, T item -> List(T)
-    pub fn ty_parent_fn(&self, ty: &AtomType, name: &String) -> Option<AtomType> {
-        let parent = self.symbols.get(name);
-
-        if parent.is_some() {
-            if let &AtomKind::Function(ref f) = &parent.unwrap().ty.kind {
-                if &f.params[0] == ty {
-                    return Some(parent.unwrap().to_owned().ty);
-                }
-            }
-        }
-        None
-    }
-
     pub fn modify_ty(&mut self, name: &String, ty: AtomType) {
         if self.symbols.contains_key(name) {
             self.symbols.get_mut(name).map(|val| val.ty = ty);
@@ -208,6 +280,8 @@ impl Enviroment {
 
             value: None,
             expected: None,
+            mutable: false,
+            span: Span::default(),
         });
     }
 
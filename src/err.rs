@@ -1,5 +1,11 @@
 // compiletime errors
 
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::IsTerminal;
+
+use crate::lexer::token::Span;
+
 #[repr(u8)]
 #[derive(Debug, Clone)]
 pub enum ErrKind {
@@ -10,55 +16,280 @@ pub enum ErrKind {
     VarAlreadyDeclared,
     OperationNotGranted,
     UnexceptedArgs,
+    UseCycle,
+    UnreachableArm,
+    MissingArm,
+}
+
+// a secondary span with a message attached, rendered under the line it points at
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub msg: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct ATErr {
     pub kind: ErrKind,
     pub msg: String,
-    pub line: u16,
-    pub column: u16,
+    pub span: Span,
+    pub file: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+// how errors are printed by the binary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json, // one json object per line for tools
+}
+
+impl ErrorFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format {
+            "human" => Some(Self::Human),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+// ansi escapes used by the renderer
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[inline]
+fn paint(s: &str, style: &str, colour: bool) -> String {
+    if colour {
+        format!("{style}{s}{RESET}")
+    } else {
+        s.to_string()
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_span(span: &Span) -> String {
+    format!(
+        "{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+        span.start.line, span.start.column, span.end.line, span.end.column
+    )
+}
+
+// colours are used when printing to a terminal unless NO_COLOR is set
+pub fn use_colour() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
 }
 
 impl ATErr {
-    pub fn get_error(&self) -> String {
+    pub fn new(kind: ErrKind, msg: String, span: Span) -> Self {
+        Self {
+            kind,
+            msg,
+            span,
+            file: String::new(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn in_file(mut self, file: &str) -> Self {
+        self.file = file.to_string();
+        self
+    }
+
+    pub fn label(mut self, span: Span, msg: &str) -> Self {
+        self.labels.push(Label {
+            span,
+            msg: msg.to_string(),
+        });
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn code(&self) -> String {
+        format!("AT{:03}", self.kind.clone() as u8)
+    }
+
+    // renders the error with the lines of source it points at, carets mark the error span
+    pub fn render(&self, source: Option<&str>, colour: bool) -> String {
+        let mut out = format!(
+            "{}{}\n",
+            paint(&format!("error[{}]", self.code()), RED, colour),
+            paint(&format!(": {}", self.msg), BOLD, colour)
+        );
+
+        let file = if self.file.is_empty() {
+            "<input>"
+        } else {
+            self.file.as_str()
+        };
+
+        // every span we have to draw, the main one uses carets
+        let mut marks = vec![(self.span, String::new(), true)];
+        for label in &self.labels {
+            marks.push((label.span, label.msg.clone(), false));
+        }
+        marks.sort_by_key(|(span, _, primary)| (span.start, !primary));
+
+        let width = marks
+            .iter()
+            .map(|(span, _, _)| span.start.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+
+        out += &format!(
+            "{gutter}{} {file}:{}:{}\n",
+            paint("-->", BLUE, colour),
+            self.span.start.line,
+            self.span.start.column
+        );
+
+        if let Some(source) = source {
+            let lines: Vec<&str> = source.lines().collect();
+            out += &format!("{gutter} {}\n", paint("|", BLUE, colour));
+
+            // marks are grouped by line so each line is only printed once
+            let mut by_line: BTreeMap<u16, Vec<(Span, String, bool)>> = BTreeMap::new();
+            for mark in marks {
+                by_line.entry(mark.0.start.line).or_default().push(mark);
+            }
+
+            let mut last_line = None;
+            for (line_num, marks) in by_line {
+                let line_idx = line_num as usize;
+                if line_idx == 0 || line_idx > lines.len() {
+                    continue;
+                }
+
+                if last_line.is_some_and(|last| line_num > last + 1) {
+                    out += &format!("{}\n", paint("...", BLUE, colour));
+                }
+                last_line = Some(line_num);
+
+                let line = lines[line_idx - 1];
+                out += &format!(
+                    "{} {} {line}\n",
+                    paint(&format!("{:>width$}", line_num), BLUE, colour),
+                    paint("|", BLUE, colour)
+                );
+
+                for (span, msg, primary) in marks {
+                    let start = span.start.column.max(1) as usize;
+
+                    // spans going over multiple lines are underlined until the end of the first line
+                    let end = if span.end.line == span.start.line {
+                        span.end.column as usize
+                    } else {
+                        line.chars().count() + 1
+                    };
+                    let len = end.saturating_sub(start).max(1);
+
+                    let (mark, style) = if primary { ("^", RED) } else { ("-", CYAN) };
+                    let underline = if msg.is_empty() {
+                        mark.repeat(len)
+                    } else {
+                        format!("{} {msg}", mark.repeat(len))
+                    };
+
+                    // keep tabs so the underline lines up with the source line
+                    let padding: String = line
+                        .chars()
+                        .take(start - 1)
+                        .map(|c| if c == '\t' { '\t' } else { ' ' })
+                        .collect();
+
+                    out += &format!(
+                        "{gutter} {} {padding}{}\n",
+                        paint("|", BLUE, colour),
+                        paint(&underline, style, colour)
+                    );
+                }
+            }
+        }
+
+        for note in &self.notes {
+            out += &format!("{gutter} {} {note}\n", paint("= help:", BOLD, colour));
+        }
+
+        out
+    }
+
+    // the error as a single line json object
+    pub fn to_json(&self) -> String {
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|label| {
+                format!(
+                    "{{\"span\":{},\"message\":{}}}",
+                    json_span(&label.span),
+                    json_str(&label.msg)
+                )
+            })
+            .collect();
+        let notes: Vec<String> = self.notes.iter().map(|note| json_str(note)).collect();
+
         format!(
-            "code:AT00{}\n{}\nat line:{}, column:{}",
+            "{{\"kind\":{},\"code\":{},\"message\":{},\"file\":{},\"span\":{},\"labels\":[{}],\"notes\":[{}]}}",
+            json_str(&format!("{:?}", self.kind)),
             self.kind.clone() as u8,
-            self.msg,
-            self.line,
-            self.column
+            json_str(&self.msg),
+            json_str(&self.file),
+            json_span(&self.span),
+            labels.join(","),
+            notes.join(",")
         )
     }
 
-    // customize later
+    // prints the error, the source is read from the file the error is in if there is one
     pub fn out_error(&self) {
-        println!("{}", self.get_error());
+        let source = fs::read_to_string(&self.file).ok();
+        println!("{}", self.render(source.as_deref(), use_colour()));
     }
 }
 
 #[macro_export]
 macro_rules! err {
+    // err!(self at span, kind, msg) points the error at a specific span instead of the current node
+    ($self: ident at $span: expr, $kind: path, $msg: expr) => {
+        $self.report(ATErr::new($kind.clone(), $msg.to_string(), $span));
+
+        return Err($kind);
+    };
+
     ($self: ident, $kind: path, $msg: literal) => {
-        ATErr {
-            kind: $kind.clone(),
-            msg: $msg.to_string(),
-            line: $self.line,
-            column: $self.column,
-        }
-        .out_error();
+        $self.report(ATErr::new($kind.clone(), $msg.to_string(), $self.span));
 
         return Err($kind);
     };
 
     ($self: ident, $kind: path, $msg: expr) => {
-        ATErr {
-            kind: $kind.clone(),
-            msg: $msg,
-            line: $self.line,
-            column: $self.column,
-        }
-        .out_error();
+        $self.report(ATErr::new($kind.clone(), $msg, $self.span));
 
         return Err($kind);
     };
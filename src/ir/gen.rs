@@ -3,7 +3,7 @@ use super::{Codegen, IROp};
 use crate::analysis::ty_as;
 use crate::enviroment::Symbol;
 use crate::err::ErrKind;
-use crate::parser::ast::{Expr, Ident, Node};
+use crate::parser::ast::{Expr, Ident, Node, Pattern};
 use crate::types::{
     can_implicitly_convert, AtomDetails, AtomKind, AtomType, BasicType, FunctionType,
 };
@@ -16,6 +16,7 @@ pub trait IRGen {
     fn replace_unknown(&mut self, node: &mut Node) -> Result<(), ErrKind>;
 
     fn gen_body(&mut self, body: Vec<Node>) -> IRRes;
+    fn gen_scope(&mut self, body: Vec<Node>, ty: &AtomType) -> IRRes;
     fn gen_prog(&mut self, exprs: Vec<Node>) -> IRRes;
     fn gen_func(
         &mut self,
@@ -29,8 +30,9 @@ pub trait IRGen {
     fn gen_expr(&mut self, expr: Node) -> IRRes;
 
     fn gen_var_declare(&mut self, name: String, expr: Node) -> IRRes;
-    fn gen_var_assign(&mut self, name: Node, expr: Node) -> IRRes;
+    fn gen_var_assign(&mut self, name: Node, expr: Node, op: Option<String>) -> IRRes;
     fn gen_binary_expr(&mut self, ty: AtomType, op: String, left: Node, right: Node) -> IRRes;
+    fn loop_var(&mut self, var: &Ident);
 }
 
 impl IRGen for Codegen {
@@ -45,6 +47,8 @@ impl IRGen for Codegen {
                         ty: node.ty.clone(),
                         value: None,
                         expected: None,
+                        mutable: true,
+                        span: node.span,
                     });
                 }
 
@@ -54,6 +58,8 @@ impl IRGen for Codegen {
                         ty: node.ty.clone(),
                         value: None,
                         expected: None,
+                        mutable: false,
+                        span: node.span,
                     });
                 }
 
@@ -97,7 +103,20 @@ impl IRGen for Codegen {
                 self.replace_unknown(&mut *left)?;
                 self.replace_unknown(&mut *right)?;
 
-                node.ty = left.ty.clone();
+                // comparisons are already bool
+                if node.ty.kind == AtomKind::Unknown {
+                    node.ty = left.ty.clone();
+                }
+            }
+
+            &mut Expr::UnaryExpr {
+                ref mut operand, ..
+            } => {
+                self.replace_unknown(&mut *operand)?;
+
+                if node.ty.kind == AtomKind::Unknown {
+                    node.ty = operand.ty.clone();
+                }
             }
 
             &mut Expr::IfExpr {
@@ -116,11 +135,48 @@ impl IRGen for Codegen {
             &mut Expr::WhileExpr {
                 ref mut condition,
                 ref mut body,
+                ..
             } => {
                 self.replace_unknown(&mut *condition)?;
                 self.replace_unknown_body(&mut *body)?;
             }
 
+            &mut Expr::MatchExpr {
+                ref mut value,
+                ref mut arms,
+            } => {
+                self.replace_unknown(&mut *value)?;
+                for (pattern, arm) in arms {
+                    self.env.child();
+                    if let Pattern::Bind(ref id) = pattern {
+                        self.loop_var(id);
+                    }
+                    self.replace_unknown(arm)?;
+                    self.env.parent();
+                }
+            }
+
+            &mut Expr::ForExpr {
+                ref var,
+                ref mut iter,
+                ref mut body,
+                ..
+            } => {
+                self.replace_unknown(&mut *iter)?;
+                self.env.child();
+                self.loop_var(var);
+                self.replace_unknown_body(&mut *body)?;
+                self.env.parent();
+            }
+
+            &mut Expr::RangeExpr {
+                ref mut start,
+                ref mut end,
+            } => {
+                self.replace_unknown(&mut *start)?;
+                self.replace_unknown(&mut *end)?;
+            }
+
             &mut Expr::Block(ref mut body) => return self.replace_unknown_body(&mut *body),
 
             &mut Expr::FnCall {
@@ -143,20 +199,49 @@ impl IRGen for Codegen {
                 node.ty = return_type;
             }
 
+            &mut Expr::Invoke {
+                ref mut callee,
+                ref mut args,
+            } => {
+                self.replace_unknown(callee)?;
+                self.replace_unknown_body(args)?;
+
+                if let AtomKind::Function(ref func) = callee.ty.kind {
+                    node.ty = (*func.return_type).clone();
+                }
+            }
+
+            &mut Expr::Lambda {
+                ref params,
+                ref mut body,
+                ..
+            } => {
+                self.env.child();
+                for param in params {
+                    self.loop_var(param);
+                }
+                self.replace_unknown_body(body)?;
+                self.env.parent();
+            }
+
             &mut Expr::As(ref mut thing) | &mut Expr::Discard(ref mut thing) => {
                 self.replace_unknown(&mut **thing)?;
             }
 
             &mut Expr::Ident(ref id) => {
-                node.ty = self.env.get_ty(id.val()).unwrap();
+                // runtime functions are only known by the analyzer
+                if let Some(ty) = self.env.get_ty(id.val()) {
+                    node.ty = ty;
+                }
             }
 
             Expr::MemberExpr { parent, child } => {
                 self.replace_unknown(&mut **parent)?;
 
-                let ty = parent.ty.get(child).unwrap();
-
-                node.ty = ty.clone();
+                // fields of an atom refering to itself are only known by the analyzer
+                if let Some(ty) = parent.ty.get(child) {
+                    node.ty = ty.clone();
+                }
             }
 
             _ => (),
@@ -193,6 +278,18 @@ impl IRGen for Codegen {
         Ok(exprs)
     }
 
+    // a body giving a value keeps its variables, the value could be one of them
+    fn gen_scope(&mut self, body: Vec<Node>, ty: &AtomType) -> IRRes {
+        self.env.child();
+        let mut body = self.gen_body(body)?;
+        self.env.parent();
+
+        if ty.kind != AtomKind::Basic(BasicType::Void) {
+            body.retain(|op| !matches!(op, IROp::Dealloc(..)));
+        }
+        Ok(body)
+    }
+
     fn gen_prog(&mut self, exprs: Vec<Node>) -> IRRes {
         self.gen_body(exprs)
     }
@@ -211,6 +308,8 @@ impl IRGen for Codegen {
                 ty: param.ty().clone(),
                 value: None,
                 expected: Some(param.ty().clone()),
+                mutable: true,
+                span: param.span(),
             });
         }
 
@@ -236,6 +335,8 @@ impl IRGen for Codegen {
             Expr::Extern { name, params } => {
                 self.gen_extern(name.val().clone(), params, name.ty().clone())
             }
+            Expr::Atom { name, fields } => Ok(vec![IROp::Atom(name, fields)]),
+            Expr::NewExpr { .. } => Ok(vec![IROp::New(expr.ty)]),
 
             Expr::Literal(lit) => Ok(vec![IROp::Const(lit)]),
 
@@ -243,8 +344,19 @@ impl IRGen for Codegen {
                 self.gen_binary_expr(expr.ty, op, *left, *right)
             }
 
-            Expr::VarDeclare { name, val } => self.gen_var_declare(name.val().clone(), *val),
-            Expr::VarAssign { name, val } => self.gen_var_assign(*name, *val),
+            Expr::UnaryExpr { op, operand } => {
+                let mut res = self.gen_expr(*operand)?;
+                res.push(match op.as_str() {
+                    "-" => IROp::Neg(expr.ty),
+                    "!" => IROp::Not,
+                    "~" => IROp::BitNot(expr.ty),
+                    o => todo!("add unary op {}", o),
+                });
+                Ok(res)
+            }
+
+            Expr::VarDeclare { name, val, .. } => self.gen_var_declare(name.val().clone(), *val),
+            Expr::VarAssign { name, val, op } => self.gen_var_assign(*name, *val, op),
             Expr::Ident(name) => Ok(vec![IROp::Load(expr.ty, name.val().clone())]),
 
             Expr::ListExpr(items) => {
@@ -264,13 +376,10 @@ impl IRGen for Codegen {
                 Ok(res)
             }
 
-            Expr::IndexExpr {
-                parent: expr,
-                index,
-            } => {
-                let expr = self.gen_expr(*expr)?;
-                let idx = self.gen_expr(*index.clone())?;
-                Ok([expr, idx, vec![IROp::LoadIdx(index.ty)]].concat())
+            Expr::IndexExpr { parent, index } => {
+                let parent = self.gen_expr(*parent)?;
+                let idx = self.gen_expr(*index)?;
+                Ok([parent, idx, vec![IROp::LoadIdx(expr.ty)]].concat())
             }
 
             Expr::FnCall { name, args } => {
@@ -285,6 +394,42 @@ impl IRGen for Codegen {
 
                 Ok(res)
             }
+            Expr::Invoke { callee, args } => {
+                let mut res: Vec<IROp> = vec![];
+                let count = args.len() as u16;
+
+                for arg in args {
+                    res.append(&mut self.gen_expr(arg)?);
+                }
+                res.append(&mut self.gen_expr(*callee)?);
+                res.push(IROp::Invoke(expr.ty, count));
+
+                Ok(res)
+            }
+
+            Expr::Lambda {
+                params,
+                body,
+                captures,
+            } => {
+                let ret = match expr.ty.kind {
+                    AtomKind::Function(func) => *func.return_type,
+                    _ => unreachable!(),
+                };
+
+                // captures and params are not deallocated with the body, the caller still has them
+                self.env.child();
+                for var in captures.iter().chain(&params) {
+                    self.loop_var(var);
+                }
+                self.env.child();
+                let body = self.gen_body(body)?;
+                self.env.parent();
+                self.env.parent();
+
+                Ok(vec![IROp::Lambda(ret, params, captures, body)])
+            }
+
             Expr::RetExpr(expr) => {
                 let mut res = vec![];
                 let mut compiled_expr = self.gen_expr(*expr.clone())?;
@@ -303,7 +448,6 @@ impl IRGen for Codegen {
                 Ok(res)
             }
 
-            Expr::PosInfo(_, _, _) => Ok(vec![]),
             Expr::Discard(dis) => {
                 let mut compiled = self.gen_expr(*dis.clone())?;
                 if dis.ty.kind != AtomKind::Basic(BasicType::Void) {
@@ -318,20 +462,16 @@ impl IRGen for Codegen {
                 alt,
             } => {
                 let mut cond = self.gen_expr(*condition)?;
-
-                // TODO func which generates scope body
-                self.env.child();
-                let body = self.gen_body(body)?;
-
-                // for sym in self.env.symbols.values() {
-                //     body.push(IROp::Dealloc(sym.ty.clone(), sym.name.clone()));
-                // }
-                self.env.parent();
-
-                let alt = if alt.is_none() {
-                    vec![]
-                } else {
-                    self.gen_expr(*alt.unwrap())?
+                let body = self.gen_scope(body, &expr.ty)?;
+
+                // an else block goes straight into the else of the if
+                let alt = match alt.map(|alt| *alt) {
+                    None => vec![],
+                    Some(Node {
+                        expr: Expr::Block(block),
+                        ..
+                    }) => self.gen_scope(block, &expr.ty)?,
+                    Some(alt) => self.gen_expr(alt)?,
                 };
 
                 let mut res = Vec::new();
@@ -340,18 +480,18 @@ impl IRGen for Codegen {
                 Ok(res)
             }
 
+            // a declaration leaves an empty block behind
+            Expr::Block(block) if block.is_empty() => Ok(vec![]),
             Expr::Block(block) => {
-                self.env.child();
-                let body = self.gen_body(block)?;
-
-                // for sym in self.env.symbols.values() {
-                //     body.push(IROp::Dealloc(sym.ty.clone(), sym.name.clone()));
-                // }
-                self.env.parent();
-                Ok(body)
+                let body = self.gen_scope(block, &expr.ty)?;
+                Ok(vec![IROp::Block(expr.ty, body)])
             }
 
-            Expr::WhileExpr { condition, body } => {
+            Expr::WhileExpr {
+                condition,
+                body,
+                label,
+            } => {
                 let mut cond = self.gen_expr(*condition)?;
 
                 self.env.child();
@@ -359,15 +499,85 @@ impl IRGen for Codegen {
 
                 let mut res = Vec::new();
                 res.append(&mut cond);
-                res.push(IROp::While(body));
+                res.push(IROp::While(label, body));
 
                 self.env.parent();
                 Ok(res)
             }
+
+            Expr::ForExpr {
+                var,
+                iter,
+                body,
+                label,
+            } => {
+                let elem = var.ty().clone();
+                let name = var.val().clone();
+                let (mut res, range) = match iter.expr {
+                    Expr::RangeExpr { start, end } => {
+                        let mut res = self.gen_expr(*start)?;
+                        res.append(&mut self.gen_expr(*end)?);
+                        (res, true)
+                    }
+                    _ => (self.gen_expr(*iter)?, false),
+                };
+
+                // the loop variable lives outside of the body so it is not deallocated with it
+                self.env.child();
+                self.loop_var(&var);
+                self.env.child();
+                let body = self.gen_body(body)?;
+                self.env.parent();
+                self.env.parent();
+
+                res.push(if range {
+                    IROp::ForRange(elem, name, label, body)
+                } else {
+                    IROp::ForEach(elem, name, label, body)
+                });
+                Ok(res)
+            }
+
+            Expr::MatchExpr { value, arms } => {
+                let value_ty = value.ty.clone();
+                let mut res = self.gen_expr(*value)?;
+
+                let mut compiled = vec![];
+                for (pattern, arm) in arms {
+                    // the name an arm gives the value lives outside of its body like a loop variable
+                    self.env.child();
+                    if let Pattern::Bind(ref id) = pattern {
+                        self.loop_var(id);
+                    }
+                    let body = match arm.expr {
+                        Expr::Block(block) => self.gen_scope(block, &expr.ty),
+                        _ => self.gen_scope(vec![arm], &expr.ty),
+                    };
+                    self.env.parent();
+                    compiled.push((pattern, body?));
+                }
+
+                res.push(IROp::Match(expr.ty, value_ty, compiled));
+                Ok(res)
+            }
+
+            Expr::Break(label) => Ok(vec![IROp::Break(label)]),
+            Expr::Continue(label) => Ok(vec![IROp::Continue(label)]),
             _ => todo!("{:#?}", expr),
         }
     }
 
+    fn loop_var(&mut self, var: &Ident) {
+        self.env.add(Symbol {
+            name: var.val().clone(),
+            ty: var.ty().clone(),
+            value: None,
+            expected: None,
+            mutable: false,
+            span: var.span(),
+        });
+    }
+
     fn gen_extern(&mut self, name: String, params: Vec<Ident>, ret: AtomType) -> IRRes {
         Ok(vec![IROp::Extern(ret, name, params)])
     }
@@ -384,6 +594,8 @@ impl IRGen for Codegen {
             ty: ty.clone(),
             value: None,
             expected: None,
+            mutable: true,
+            span: expr.span,
         });
 
         res.append(&mut g);
@@ -392,39 +604,50 @@ impl IRGen for Codegen {
         Ok(res)
     }
 
-    fn gen_var_assign(&mut self, name: Node, expr: Node) -> IRRes {
+    fn gen_var_assign(&mut self, name: Node, expr: Node, op: Option<String>) -> IRRes {
         let mut res = vec![];
+        let target_ty = name.ty.clone();
         res.append(&mut self.gen_expr(name)?);
         let mut compiled_expr = self.gen_expr(expr.clone())?;
         let ty = expr.ty;
 
         res.append(&mut compiled_expr);
-        res.push(IROp::Set(ty));
+        match op {
+            Some(op) => res.push(IROp::Update(Box::new(binary_op(&op, target_ty)))),
+            None => res.push(IROp::Set(ty)),
+        }
         Ok(res)
     }
 
     fn gen_binary_expr(&mut self, ty: AtomType, op: String, left: Node, right: Node) -> IRRes {
         let mut res: IR = vec![];
-        let mut lhs = self.gen_expr(left.clone())?;
-        let mut rhs = self.gen_expr(right)?;
-        res.append(&mut rhs);
-        res.append(&mut lhs);
-        if op.as_str() == "<" || op.as_str() == "<=" {
-            res.reverse();
-        }
-        res.append(&mut vec![match op.as_str() {
-            "+" => IROp::Add(ty),
-            "-" => IROp::Sub(ty),
-            "*" => IROp::Mul(ty),
-            "/" => IROp::Div(ty),
-            "%" => IROp::Mod(ty),
-            ">" | "<" => IROp::Comp,
-            ">=" | "<=" => IROp::EComp,
-            "==" => IROp::Eq,
-            "&&" => IROp::And,
-            "||" => IROp::Or,
-            o => todo!("add op {}", o),
-        }]);
+        res.append(&mut self.gen_expr(left)?);
+        res.append(&mut self.gen_expr(right)?);
+        res.push(binary_op(&op, ty));
         Ok(res)
     }
 }
+
+fn binary_op(op: &str, ty: AtomType) -> IROp {
+    match op {
+        "+" => IROp::Add(ty),
+        "-" => IROp::Sub(ty),
+        "*" => IROp::Mul(ty),
+        "/" => IROp::Div(ty),
+        "%" => IROp::Mod(ty),
+        "&" => IROp::BitAnd(ty),
+        "|" => IROp::BitOr(ty),
+        "^" => IROp::BitXor(ty),
+        "<<" => IROp::Shl(ty),
+        ">>" => IROp::Shr(ty),
+        "<" => IROp::Lt,
+        "<=" => IROp::Le,
+        ">" => IROp::Gt,
+        ">=" => IROp::Ge,
+        "==" => IROp::Eq,
+        "!=" => IROp::NotEq,
+        "&&" => IROp::And,
+        "||" => IROp::Or,
+        o => todo!("add op {}", o),
+    }
+}
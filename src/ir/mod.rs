@@ -1,4 +1,4 @@
-use crate::parser::ast::{Ident, Literal};
+use crate::parser::ast::{Ident, Literal, Pattern};
 
 use crate::enviroment::Enviroment;
 use crate::types::{self, AtomKind, AtomType, BasicType};
@@ -11,8 +11,11 @@ pub enum IROp {
     Import(AtomType, String, String, Vec<AtomType>), // ty mod fun arg count
     Extern(AtomType, String, Vec<Ident>),
     Def(AtomType, String, Vec<Ident>, Vec<IROp>),
+    Atom(String, Vec<Ident>), // name fields
 
     Call(AtomType, u16),
+    Invoke(AtomType, u16), // calls the function value on top of the args
+    Lambda(AtomType, Vec<Ident>, Vec<Ident>, Vec<IROp>), // ret params captures body, makes a function value
     Ret(AtomType),
 
     Add(AtomType),
@@ -21,9 +24,22 @@ pub enum IROp {
     Div(AtomType),
     Mod(AtomType),
 
-    Comp, // acts like GE to peform LE switch left and right
-    EComp,
+    BitAnd(AtomType),
+    BitOr(AtomType),
+    BitXor(AtomType),
+    Shl(AtomType),
+    Shr(AtomType),
+
+    Neg(AtomType),
+    Not,
+    BitNot(AtomType),
+
+    Lt,
+    Le,
+    Gt,
+    Ge,
     Eq,
+    NotEq,
     And,
     Or,
 
@@ -31,15 +47,24 @@ pub enum IROp {
     List(AtomType, Vec<Vec<IROp>>), // each item is a bunch of operations
     Conv(AtomType, AtomType),
     Alloc(AtomType, String),
+    New(AtomType),             // allocates an atom
     Dealloc(AtomType, String), // when allocing a var with a new type we dealloc the old val
     Store(AtomType, String),
     Set(AtomType),
-    Load(AtomType, String),     // load loads an id
+    Update(Box<IROp>), // applies the binary op to the target and the value then sets the target
+    Load(AtomType, String), // load loads an id
     LoadProp(AtomType, String), // load prop loads a property from the id
-    LoadIdx(AtomType),          // loads an index
+    LoadIdx(AtomType), // loads an index
 
     If(AtomType, Vec<IROp>, Vec<IROp>),
-    While(Vec<IROp>),
+    Block(AtomType, Vec<IROp>), // a block of its own, giving the value of its last op
+    Match(AtomType, AtomType, Vec<(Pattern, Vec<IROp>)>), // the type it gives and the type of the matched value
+    // loops carry the label break and continue use to name them
+    While(Option<String>, Vec<IROp>),
+    ForRange(AtomType, String, Option<String>, Vec<IROp>), // counts the variable from start to end
+    ForEach(AtomType, String, Option<String>, Vec<IROp>), // sets the variable to every item of a list
+    Break(Option<String>),
+    Continue(Option<String>),
     Pop,
 }
 use crate::err::ATErr;
@@ -60,8 +85,19 @@ pub fn get_op_type(op: &IROp) -> AtomType {
         Import(t, _, _, _) => t,
         Extern(t, _, _) => t,
         Def(t, _, _, _) => t,
+        Atom(_, _) => &void,
 
         Call(t, _) => t,
+        Invoke(t, _) => t,
+        Lambda(t, params, _, _) => {
+            return AtomType {
+                kind: AtomKind::Function(types::FunctionType {
+                    params: params.iter().map(|param| param.ty().clone()).collect(),
+                    return_type: Box::new(t.clone()),
+                }),
+                details: None,
+            }
+        }
         Ret(t) => t,
 
         Add(t) => t,
@@ -70,12 +106,22 @@ pub fn get_op_type(op: &IROp) -> AtomType {
         Div(t) => t,
         Mod(t) => t,
 
+        BitAnd(t) => t,
+        BitOr(t) => t,
+        BitXor(t) => t,
+        Shl(t) => t,
+        Shr(t) => t,
+
+        Neg(t) => t,
+        Not => &bool,
+        BitNot(t) => t,
+
         And => &bool,
         Or => &bool,
 
-        Comp => &bool,
-        EComp => &bool,
+        Lt | Le | Gt | Ge => &bool,
         Eq => &bool,
+        NotEq => &bool,
 
         List(ty, _) => {
             return AtomType {
@@ -88,14 +134,18 @@ pub fn get_op_type(op: &IROp) -> AtomType {
         Conv(t, _) => t,
         Store(t, _) => t,
         Set(t) => t,
+        Update(op) => return get_op_type(op),
         Load(t, _) => t,
         LoadProp(t, _) => t,
         LoadIdx(t) => t,
         // Get(t) => t,
         Alloc(t, _) => t,
+        New(t) => t,
         Dealloc(t, _) => t,
         If(t, _, _) => t,
-        While(_) => &void,
+        Block(t, _) => t,
+        Match(t, _, _) => t,
+        While(..) | ForRange(..) | ForEach(..) | Break(_) | Continue(_) => &void,
         Pop => &void,
     }
     .clone()
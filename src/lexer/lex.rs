@@ -1,47 +1,407 @@
 use super::token::Token;
 use super::*;
 use crate::err::ErrKind;
+use crate::types::BasicType;
 
-pub fn is_num(c: char) -> bool {
-    return "01234.56789".contains(c);
-}
 pub fn is_id(c: char) -> bool {
-    return !(" \t\n+-*/<&|>=@#%:!?$,.[{('`)}]").contains(c);
+    return !(" \t\n+-*/<&|>=@#%:!?$,.[{('`)}]~").contains(c);
 }
 
 impl Lexer {
-    pub fn parse_num(&mut self, x: String) -> Token {
-        if x.contains('.') {
-            return Token::Float(x.parse().unwrap());
+    // scans a number literal, the - of a negative one is already eaten
+    // 255 0xFF 0o377 0b1111_1111 1_000_000 2.5 1e9 2.5e-3 with an optional type suffix 10u8 2.5f64
+    fn number(&mut self, negative: bool) -> Token {
+        let begin = self.pos;
+        let radix = match (self.at(), self.peek(1)) {
+            ('0', Some('x')) => 16,
+            ('0', Some('o')) => 8,
+            ('0', Some('b')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.eat();
+            self.eat();
+        }
+
+        let mut digits = self.digits(radix);
+        let mut float = false;
+        if radix == 10 {
+            // a . is only part of the number if a digit follows it
+            if self.not_eof()
+                && self.at() == '.'
+                && self.peek(1).is_some_and(|c| c.is_ascii_digit())
+            {
+                digits.push(self.eat());
+                digits += &self.digits(10);
+                float = true;
+            }
+
+            let sign = matches!(self.peek(1), Some('+') | Some('-'));
+            let exponent = self.peek(if sign { 2 } else { 1 });
+            if self.not_eof()
+                && (self.at() == 'e' || self.at() == 'E')
+                && exponent.is_some_and(|c| c.is_ascii_digit())
+            {
+                digits.push(self.eat());
+                if sign {
+                    digits.push(self.eat());
+                }
+                digits += &self.digits(10);
+                float = true;
+            }
+        }
+
+        let mut suffix = String::new();
+        // .. after a number is a range, 0..10
+        while self.not_eof()
+            && (self.at().is_ascii_alphanumeric() || self.at() == '.' && self.peek(1) != Some('.'))
+        {
+            suffix.push(self.eat());
+        }
+        let text = format!(
+            "{}{}",
+            if negative { "-" } else { "" },
+            &self.code[begin..self.pos]
+        );
+
+        if digits.is_empty() {
+            return self.err(
+                format!("invaild number '{}', expected digits after its base", text),
+                ErrKind::UnknownCharE,
+            );
+        }
+        let ty = if suffix.is_empty() {
+            if float {
+                BasicType::Float
+            } else {
+                BasicType::Int
+            }
+        } else {
+            match BasicType::from_suffix(&suffix) {
+                Some(ty) => ty,
+                None if suffix.contains('.') => {
+                    return self.err(format!("invaild number '{}'", text), ErrKind::UnknownCharE)
+                }
+                None if suffix.starts_with(|c: char| c.is_ascii_digit()) => {
+                    return self.err(
+                        format!(
+                            "invaild number '{}', {} is not a base {} digit",
+                            text,
+                            &suffix[..1],
+                            radix
+                        ),
+                        ErrKind::UnknownCharE,
+                    )
+                }
+                None => {
+                    return self.err(
+                        format!("unknown number suffix '{}'", suffix),
+                        ErrKind::UnknownCharE,
+                    )
+                }
+            }
+        };
+
+        if ty.is_float() {
+            if radix != 10 {
+                return self.err(
+                    format!("invaild float '{}', floats are written in base 10", text),
+                    ErrKind::UnknownCharE,
+                );
+            }
+            let max = if ty == BasicType::Float {
+                f32::MAX as f64
+            } else {
+                f64::MAX
+            };
+            return match digits.parse::<f64>() {
+                Ok(f) if f > max => self.err(
+                    format!("'{}' does not fit in {}", text, ty),
+                    ErrKind::UnknownCharE,
+                ),
+                Ok(f) if negative => Token::Float(-f, ty),
+                Ok(f) => Token::Float(f, ty),
+                Err(_) => self.err(format!("invaild float '{}'", text), ErrKind::UnknownCharE),
+            };
+        }
+        if float {
+            return self.err(
+                format!("invaild int '{}', {} has no decimals", text, ty),
+                ErrKind::UnknownCharE,
+            );
+        }
+
+        // anything too big for a u64 does not fit in any int
+        let value = u64::from_str_radix(&digits, radix).ok().map(|i| {
+            if negative {
+                -(i as i128)
+            } else {
+                i as i128
+            }
+        });
+        match value {
+            Some(i) if ty.min() <= i && i <= ty.max() => Token::Int(i, ty),
+            Some(_) if negative && ty.min() == 0 => self.err(
+                format!("'{}' does not fit in {}, it cannot be negative", text, ty),
+                ErrKind::UnknownCharE,
+            ),
+            Some(i) if suffix.is_empty() => {
+                match [BasicType::I64, BasicType::U64]
+                    .iter()
+                    .find(|ty| ty.min() <= i && i <= ty.max())
+                {
+                    Some(wider) => self.err(
+                        format!(
+                            "'{}' does not fit in int, add a suffix like {}{}",
+                            text, text, wider
+                        ),
+                        ErrKind::UnknownCharE,
+                    ),
+                    None => self.err(
+                        format!("'{}' does not fit in any int", text),
+                        ErrKind::UnknownCharE,
+                    ),
+                }
+            }
+            None => self.err(
+                format!("'{}' does not fit in any int", text),
+                ErrKind::UnknownCharE,
+            ),
+            _ => self.err(
+                format!("'{}' does not fit in {}", text, ty),
+                ErrKind::UnknownCharE,
+            ),
+        }
+    }
+
+    // digits of a number in radix, _ can seperate them and is skipped
+    fn digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+        while self.not_eof() && (self.at().is_digit(radix) || self.at() == '_') {
+            let c = self.eat();
+            if c != '_' {
+                digits.push(c);
+            }
+        }
+        digits
+    }
+
+    // x is whatever was between the quotes of a char literal
+    pub fn parse_char(&mut self, x: String) -> Token {
+        let mut chars = x.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii() => Token::Char(c as u8),
+            (Some(c), None) => self.err(
+                format!(
+                    "invaild char '{}', it is not ascii and a char is a single byte, use \"{}\" for a str",
+                    c, c
+                ),
+                ErrKind::UnknownCharE,
+            ),
+            _ => self.err(
+                format!(
+                    "invaild char '{}', a char is a single ascii character, use \"{}\" for a str",
+                    x, x
+                ),
+                ErrKind::UnknownCharE,
+            ),
+        }
+    }
+
+    // the char an escape in a str or char stands for, the \ is already eaten
+    fn escape(&mut self) -> Result<char, Token> {
+        if !self.not_eof() {
+            return Err(self.err(
+                "expected an escape after \\".to_string(),
+                ErrKind::UnknownCharE,
+            ));
+        }
+
+        match self.eat() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '{' => Ok('{'),
+            '}' => Ok('}'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            'u' => {
+                if !self.not_eof() || self.at() != '{' {
+                    return Err(self.err(
+                        "expected { after \\u, like \\u{1F600}".to_string(),
+                        ErrKind::UnknownCharE,
+                    ));
+                }
+                self.eat();
+
+                let mut hex = String::new();
+                while self.not_eof() && self.at().is_ascii_hexdigit() {
+                    hex.push(self.eat());
+                }
+                if !self.not_eof() || self.at() != '}' {
+                    return Err(self.err(
+                        format!("expected }} to close \\u{{{}", hex),
+                        ErrKind::UnknownCharE,
+                    ));
+                }
+                self.eat();
+
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => Ok(c),
+                    None => Err(self.err(
+                        format!("\\u{{{}}} is not a unicode character", hex),
+                        ErrKind::UnknownCharE,
+                    )),
+                }
+            }
+            c => Err(self.err(
+                format!(
+                    "unknown escape \\{}, escapes are \\n \\t \\r \\0 \\\\ \\\" \\' \\{{ \\}} \\u{{..}}",
+                    c
+                ),
+                ErrKind::UnknownCharE,
+            )),
+        }
+    }
+
+    // lexes a str after its opening quote or after the } closing an interpolated expression
+    // stops at a { returning the text before it as a StrPart, the expression is lexed next
+    fn str_rest(&mut self) -> Token {
+        let mut res = String::new();
+        let mut invaild = None; // first bad escape, the rest of the str is still eaten
+        while self.not_eof() && self.at() != '"' && self.at() != '{' {
+            match self.eat() {
+                '\\' => match self.escape() {
+                    Ok(c) => res.push(c),
+                    Err(err) => invaild = invaild.or(Some(err)),
+                },
+                c => res.push(c),
+            }
+        }
+
+        if !self.not_eof() {
+            return self.err(
+                format!(
+                    "reached end of file and didnt finish string started at line {}, colmun {}",
+                    self.start.line, self.start.column
+                ),
+                ErrKind::UnknownCharE,
+            );
+        }
+        let interp = self.eat() == '{';
+        if interp {
+            self.interps.push(0);
+        }
+
+        match invaild {
+            Some(err) => err,
+            None if interp => Token::StrPart(res),
+            None => Token::Str(res),
+        }
+    }
+
+    // r" or r#" starts a raw str
+    fn raw_start(&self) -> bool {
+        matches!(self.peek(1), Some('"') | Some('#'))
+    }
+
+    // r"..." is taken as it is written, r#"..."# can also hold a "
+    fn raw_str(&mut self) -> Token {
+        self.eat();
+        let mut hashes = 0;
+        while self.not_eof() && self.at() == '#' {
+            self.eat();
+            hashes += 1;
+        }
+        if !self.not_eof() || self.at() != '"' {
+            return self.err(
+                "expected \" to start the raw str".to_string(),
+                ErrKind::UnknownCharE,
+            );
+        }
+        self.eat();
+
+        let end = format!("\"{}", "#".repeat(hashes));
+        match self.code[self.pos..].find(&end) {
+            Some(len) => {
+                let res = self.code[self.pos..self.pos + len].to_string();
+                for _ in 0..len + end.len() {
+                    self.eat();
+                }
+                Token::Str(res)
+            }
+            None => {
+                while self.not_eof() {
+                    self.eat();
+                }
+                self.err(
+                    format!("raw str is never closed, expected {}", end),
+                    ErrKind::UnknownCharE,
+                )
+            }
         }
-        return Token::Int(x.parse().unwrap());
+    }
+
+    // an operator followed by = assigns its result, x += 1
+    fn assign_op(&mut self, mut op: String) -> Token {
+        if self.not_eof() && self.at() == '=' {
+            op.push(self.eat());
+        }
+        Token::Operator(op)
     }
 
     pub fn tokenize(&mut self) -> Token {
+        let tok = self.lex_token();
+        self.last_end = self.pos;
+        self.after_value = matches!(
+            tok,
+            Token::Int(..)
+                | Token::Float(..)
+                | Token::Char(_)
+                | Token::Str(_)
+                | Token::Bool(_)
+                | Token::Ident(_)
+                | Token::Exec
+                // set new! declares a constructor taking no args, fn! a lambda taking none
+                | Token::NewKw
+                | Token::FnKw
+                | Token::RightParen
+                | Token::RightBrace
+                | Token::RightBracket
+        );
+        tok
+    }
+
+    fn lex_token(&mut self) -> Token {
+        // the rest of a str after an interpolated expression, spaces in it are kept
+        if self.str_open {
+            self.str_open = false;
+            self.start = self.current_pos();
+            return self.str_rest();
+        }
         loop {
             if !self.not_eof() {
+                self.start = self.current_pos();
                 return Token::EOF;
             }
             match self.at() {
-                ' ' | '\t' => {
+                ' ' | '\t' | '\n' => {
                     self.eat();
                 }
-                '\n' => {
-                    self.eat();
-                    self.line += 1;
-                    self.column = 0;
-                }
                 _ => break,
             }
         }
+        self.start = self.current_pos();
 
         match self.at() {
             '#' => {
                 self.eat();
-                if self.at() == '*' {
+                if self.not_eof() && self.at() == '*' {
                     while self.not_eof() {
                         // eats then checks if its * and the next char is #
-                        if self.eat() == '*' && self.at() == '#' {
+                        if self.eat() == '*' && self.not_eof() && self.at() == '#' {
                             self.eat();
                             break;
                         }
@@ -52,60 +412,71 @@ impl Lexer {
                     }
                 }
 
-                self.tokenize()
-            }
-            '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                let mut res = String::from("");
-                while self.not_eof() && is_num(self.at()) {
-                    res.push(self.eat())
-                }
-                self.parse_num(res)
+                self.lex_token()
             }
+            '0'..='9' => self.number(false),
 
-            '"' | '\'' => {
-                let op = self.eat();
-
-                let start_line = self.line;
-                let start_column = self.column;
+            '\'' => {
+                self.eat();
 
                 let mut res = String::from("");
-
-                while self.not_eof() && self.at() != op {
-                    res.push(self.eat());
+                let mut invaild = None; // first bad escape, the rest of the char is still eaten
+                while self.not_eof() && self.at() != '\'' && self.at() != '\n' {
+                    match self.eat() {
+                        '\\' => match self.escape() {
+                            Ok(c) => res.push(c),
+                            Err(err) => invaild = invaild.or(Some(err)),
+                        },
+                        c => res.push(c),
+                    }
                 }
 
-                if self.not_eof() && self.at() == op {
+                if self.not_eof() && self.at() == '\'' {
                     self.eat();
                 } else {
                     return self.err(
-                        format!(
-                            "reached end of file and didnt finish string started at line {}, colmun {}",
-                            start_line,
-                            start_column
-                        ),
-                        ErrKind::UnknownCharE
+                        format!("char '{}' is never closed", res),
+                        ErrKind::UnknownCharE,
                     );
                 }
+                match invaild {
+                    Some(err) => err,
+                    None => self.parse_char(res),
+                }
+            }
 
-                Token::Str(res)
+            'r' if self.raw_start() => self.raw_str(),
+
+            '"' => {
+                self.eat();
+                self.str_rest()
             }
             '=' => {
                 self.eat();
                 if self.not_eof() && self.at() == '=' {
                     self.eat();
                     Token::Operator("==".to_string())
+                } else if self.not_eof() && self.at() == '>' {
+                    self.eat();
+                    Token::FatArrow
                 } else {
                     Token::Operator("=".to_string())
                 }
             }
 
+            // a - that cannot subtract from anything starts a negative number
+            '-' if !self.after_value && self.peek(1).is_some_and(|c| c.is_ascii_digit()) => {
+                self.eat();
+                self.number(true)
+            }
+
             '-' => {
                 self.eat();
                 if self.not_eof() && self.at() == '>' {
                     self.eat();
                     Token::Access
                 } else {
-                    Token::Operator('-'.to_string())
+                    self.assign_op("-".to_string())
                 }
             }
 
@@ -113,18 +484,23 @@ impl Lexer {
                 let mut op = self.eat().to_string();
                 if self.not_eof() && self.at() == op.as_bytes()[0].into() {
                     op.push(self.eat());
+                    return Token::Operator(op);
                 }
-                Token::Operator(op)
+                self.assign_op(op)
             }
             '+' | '*' | '/' | '%' | '^' => {
                 let op = self.eat();
-                Token::Operator(op.to_string())
+                self.assign_op(op.to_string())
             }
 
             '<' | '>' => {
                 let op = self.eat();
 
-                if self.not_eof() && self.at() == '=' {
+                // << and >>, <<= and >>=
+                if self.not_eof() && self.at() == op {
+                    self.eat();
+                    self.assign_op(format!("{}{}", op, op))
+                } else if self.not_eof() && self.at() == '=' {
                     let mut op = op.to_string();
                     op.push(self.eat());
                     Token::Operator(op)
@@ -145,11 +521,25 @@ impl Lexer {
 
             '{' => {
                 self.eat();
+                if let Some(depth) = self.interps.last_mut() {
+                    *depth += 1;
+                }
                 Token::LeftBracket
             }
 
+            // closes an interpolated expression, the str goes on after it
+            '}' if self.interps.last() == Some(&0) => {
+                self.eat();
+                self.interps.pop();
+                self.str_open = true;
+                Token::InterpEnd
+            }
+
             '}' => {
                 self.eat();
+                if let Some(depth) = self.interps.last_mut() {
+                    *depth -= 1;
+                }
                 Token::RightBracket
             }
 
@@ -165,7 +555,12 @@ impl Lexer {
 
             ':' => {
                 self.eat();
-                Token::Colon
+                if self.not_eof() && self.at() == ':' {
+                    self.eat();
+                    Token::DoubleColon
+                } else {
+                    Token::Colon
+                }
             }
 
             ',' => {
@@ -173,16 +568,39 @@ impl Lexer {
                 Token::Comma
             }
 
+            '.' if self.peek(1) == Some('.') => {
+                self.eat();
+                self.eat();
+                Token::DotDot
+            }
+
             '.' => {
                 self.eat();
                 Token::Dot
             }
 
-            '!' => {
+            '!' if self.peek(1) == Some('=') => {
+                self.eat();
+                self.eat();
+                Token::Operator("!=".to_string())
+            }
+
+            // f! calls f, a ! that does not touch a value is a not
+            '!' if self.after_value && self.pos == self.last_end => {
                 self.eat();
                 Token::Exec
             }
 
+            '!' => {
+                self.eat();
+                Token::Operator("!".to_string())
+            }
+
+            '~' => {
+                self.eat();
+                Token::Operator("~".to_string())
+            }
+
             '@' => {
                 self.eat();
                 Token::Dash
@@ -203,9 +621,20 @@ impl Lexer {
                         "if" => Token::IfKw,
                         "else" => Token::ElseKw,
                         "while" => Token::WhileKw,
+                        "for" => Token::ForKw,
+                        "in" => Token::InKw,
+                        "match" => Token::MatchKw,
+                        "fn" => Token::FnKw,
                         "break" => Token::BreakKw,
                         "continue" => Token::Continuekw,
                         "ret" => Token::RetKw,
+                        "atom" => Token::AtomKw,
+                        "bond" => Token::BondKw,
+                        "state" => Token::StateKw,
+                        "part" => Token::PartKw,
+                        "new" => Token::NewKw,
+                        "mod" => Token::ModKw,
+                        "not" => Token::Operator("!".to_string()),
                         // bools
                         "true" => Token::Bool(true),
                         "false" => Token::Bool(false),
@@ -219,3 +648,219 @@ impl Lexer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(code: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(code.to_string(), String::new());
+        let mut tokens = vec![];
+        loop {
+            match lexer.tokenize() {
+                Token::EOF => return tokens,
+                tok => tokens.push(tok),
+            }
+        }
+    }
+
+    fn token(code: &str) -> Token {
+        let mut tokens = tokens(code);
+        assert_eq!(tokens.len(), 1, "{} lexed into {:?}", code, tokens);
+        tokens.remove(0)
+    }
+
+    // the message of the first error lexing code reports
+    fn error(code: &str) -> String {
+        let mut lexer = Lexer::new(code.to_string(), String::new());
+        while lexer.tokenize() != Token::EOF {}
+        lexer
+            .errors
+            .first()
+            .expect("no error was reported")
+            .msg
+            .clone()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(token("255"), Token::Int(255, BasicType::Int));
+        assert_eq!(token("0xFF"), Token::Int(255, BasicType::Int));
+        assert_eq!(token("0o377"), Token::Int(255, BasicType::Int));
+        assert_eq!(token("0b1111_1111"), Token::Int(255, BasicType::Int));
+        assert_eq!(token("1_000_000"), Token::Int(1_000_000, BasicType::Int));
+        assert_eq!(token("10u8"), Token::Int(10, BasicType::U8));
+        assert_eq!(token("-128i8"), Token::Int(-128, BasicType::I8));
+        assert_eq!(token("2.5"), Token::Float(2.5, BasicType::Float));
+        assert_eq!(token("2.5e-3"), Token::Float(2.5e-3, BasicType::Float));
+        assert_eq!(token("1e9"), Token::Float(1e9, BasicType::Float));
+        assert_eq!(token("2.5f64"), Token::Float(2.5, BasicType::F64));
+    }
+
+    #[test]
+    fn numbers_next_to_other_tokens() {
+        assert_eq!(
+            tokens("0..10"),
+            vec![
+                Token::Int(0, BasicType::Int),
+                Token::DotDot,
+                Token::Int(10, BasicType::Int)
+            ]
+        );
+        // a - after a value subtracts
+        assert_eq!(
+            tokens("x -1"),
+            vec![
+                Token::Ident("x".to_string()),
+                Token::Operator("-".to_string()),
+                Token::Int(1, BasicType::Int)
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_that_do_not_fit() {
+        assert_eq!(error("256u8"), "'256u8' does not fit in u8");
+        assert_eq!(error("1e39"), "'1e39' does not fit in float");
+        assert_eq!(error("1e309f64"), "'1e309f64' does not fit in f64");
+        assert_eq!(
+            error("-1u8"),
+            "'-1u8' does not fit in u8, it cannot be negative"
+        );
+        assert_eq!(
+            error("3000000000"),
+            "'3000000000' does not fit in int, add a suffix like 3000000000i64"
+        );
+        assert_eq!(
+            error("99999999999999999999"),
+            "'99999999999999999999' does not fit in any int"
+        );
+    }
+
+    #[test]
+    fn invaild_numbers() {
+        assert_eq!(
+            error("0x"),
+            "invaild number '0x', expected digits after its base"
+        );
+        assert_eq!(
+            error("0b102"),
+            "invaild number '0b102', 2 is not a base 2 digit"
+        );
+        assert_eq!(error("10q"), "unknown number suffix 'q'");
+        assert_eq!(error("2.5u8"), "invaild int '2.5u8', u8 has no decimals");
+        assert_eq!(
+            error("0o7f32"),
+            "invaild float '0o7f32', floats are written in base 10"
+        );
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(token("'a'"), Token::Char(b'a'));
+        assert_eq!(token("'\\n'"), Token::Char(b'\n'));
+        assert_eq!(token("'\\''"), Token::Char(b'\''));
+        assert_eq!(token("'\\0'"), Token::Char(0));
+        assert_eq!(
+            error("'ab'"),
+            "invaild char 'ab', a char is a single ascii character, use \"ab\" for a str"
+        );
+        assert_eq!(error("'a"), "char 'a' is never closed");
+        assert_eq!(
+            error("'é'"),
+            "invaild char 'é', it is not ascii and a char is a single byte, use \"é\" for a str"
+        );
+        // a char outside of ascii is one column wide however many bytes it takes
+        let mut lexer = Lexer::new("'é'".to_string(), String::new());
+        lexer.tokenize();
+        assert_eq!(lexer.span().end.column - lexer.span().start.column, 3);
+        assert_eq!(token("\"héllo\""), Token::Str("héllo".to_string()));
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            token(r#""a\tb\n\\\"\{\}\0""#),
+            Token::Str("a\tb\n\\\"{}\0".to_string())
+        );
+        assert_eq!(token(r#""\u{1F600}""#), Token::Str("\u{1F600}".to_string()));
+        assert!(error(r#""\q""#).starts_with("unknown escape \\q"));
+        assert_eq!(
+            error(r#""\u{110000}""#),
+            "\\u{110000} is not a unicode character"
+        );
+        assert_eq!(error(r#""\u1""#), "expected { after \\u, like \\u{1F600}");
+    }
+
+    #[test]
+    fn interps() {
+        assert_eq!(
+            tokens("\"a{x}b\""),
+            vec![
+                Token::StrPart("a".to_string()),
+                Token::Ident("x".to_string()),
+                Token::InterpEnd,
+                Token::Str("b".to_string())
+            ]
+        );
+        // the text after an expression keeps its spaces, braces inside the expression are its own
+        assert_eq!(
+            tokens("\"{ {} } y\""),
+            vec![
+                Token::StrPart(String::new()),
+                Token::LeftBracket,
+                Token::RightBracket,
+                Token::InterpEnd,
+                Token::Str(" y".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_strs() {
+        assert_eq!(token(r#"r"a\nb""#), Token::Str("a\\nb".to_string()));
+        assert_eq!(
+            token(r##"r#"say "hi""#"##),
+            Token::Str("say \"hi\"".to_string())
+        );
+        assert_eq!(error(r#"r#"a"#), "raw str is never closed, expected \"#");
+        // an r not followed by a quote is an id
+        assert_eq!(token("r"), Token::Ident("r".to_string()));
+    }
+
+    #[test]
+    fn exec_or_not() {
+        // f! calls f
+        assert_eq!(
+            tokens("f!"),
+            vec![Token::Ident("f".to_string()), Token::Exec]
+        );
+        // set new! declares a constructor, fn! a lambda taking no args
+        assert_eq!(tokens("new!"), vec![Token::NewKw, Token::Exec]);
+        assert_eq!(tokens("fn!"), vec![Token::FnKw, Token::Exec]);
+        // a ! that does not touch a value is a not
+        assert_eq!(
+            tokens("f !x"),
+            vec![
+                Token::Ident("f".to_string()),
+                Token::Operator("!".to_string()),
+                Token::Ident("x".to_string())
+            ]
+        );
+        assert_eq!(
+            tokens("!x"),
+            vec![
+                Token::Operator("!".to_string()),
+                Token::Ident("x".to_string())
+            ]
+        );
+        assert_eq!(
+            tokens("a != b"),
+            vec![
+                Token::Ident("a".to_string()),
+                Token::Operator("!=".to_string()),
+                Token::Ident("b".to_string())
+            ]
+        );
+    }
+}
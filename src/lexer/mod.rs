@@ -3,56 +3,81 @@ use crate::err::{ATErr, ErrKind};
 pub mod lex;
 pub mod token;
 
-use self::token::Token;
+use self::token::{Pos, Span, Token};
 #[derive(Debug, Clone)]
 pub struct Lexer {
     line: u16,
     column: u16,
+    start: Pos, // where the token currently being lexed starts
     code: String,
     pos: usize,
+    interps: Vec<u16>, // braces opened inside each interpolated expression being lexed
+    str_open: bool,    // an interpolated expression was just closed, the str goes on
+    after_value: bool, // the last token ends a value, a - after it subtracts
+    last_end: usize,   // where the last token ended
+    pub file: String,
     pub errors: Vec<ATErr>,
 }
 
 impl Lexer {
-    pub fn new(code: String) -> Self {
+    pub fn new(code: String, file: String) -> Self {
         Self {
-            line: 0,
-            column: 0,
+            line: 1,
+            column: 1,
+            start: Pos::default(),
             pos: 0,
+            interps: Vec::new(),
+            str_open: false,
+            after_value: false,
+            last_end: 0,
             code,
+            file,
             errors: Vec::new(),
         }
     }
 
-    fn at(&mut self) -> char {
-        let c = (&self.code).as_bytes()[self.pos].clone() as char;
-        if &c == &'\n' {
-            self.line += 1;
-            self.column = 0;
-        }
+    fn at(&self) -> char {
+        self.code[self.pos..].chars().next().unwrap()
+    }
 
-        c.clone()
+    // the char ahead of the current one
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.code
+            .as_bytes()
+            .get(self.pos + ahead)
+            .map(|c| *c as char)
     }
 
     fn eat(&mut self) -> char {
-        self.pos += 1;
-        self.column += 1;
-        (&self.code).as_bytes()[self.pos - 1].clone() as char
+        // a char outside of ascii takes more than one byte but only one column
+        let c = self.at();
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        c
     }
 
     fn not_eof(&self) -> bool {
-        self.code.len() - 1 >= self.pos
+        self.pos < self.code.len()
+    }
+
+    #[inline]
+    fn current_pos(&self) -> Pos {
+        Pos::new(self.line, self.column)
+    }
+
+    // span of the last token returned by tokenize
+    pub fn span(&self) -> Span {
+        Span::new(self.start, self.current_pos())
     }
 
     fn err(&mut self, msg: String, kind: ErrKind) -> Token {
-        let err = ATErr {
-            kind,
-            msg,
-            line: self.line,
-            column: self.column,
-        };
-        self.errors.push(err.clone());
-        err.out_error();
-        Token::Err(err.get_error())
+        let err = ATErr::new(kind, msg.clone(), self.span()).in_file(&self.file);
+        self.errors.push(err);
+        Token::Err(msg)
     }
 }
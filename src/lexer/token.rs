@@ -1,13 +1,18 @@
+use crate::types::BasicType;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Operator(String),
     // convert these into literal
-    Int(i32),
-    Float(f32),
+    Int(i128, BasicType), // value and the type its suffix names, int without one
+    Float(f64, BasicType),
+    Char(u8),
     Str(String),
+    StrPart(String), // text of an interpolated str before a {expression}, the rest ends with a Str
+    InterpEnd,       // } closing a {expression}, the rest of the str comes after it
     Bool(bool),
     Ident(String),
-    Err(String), // error code and msg
+    Err(String), // msg of an error the lexer already reported
     LeftParen,
     RightParen,
     LeftBrace,
@@ -17,17 +22,62 @@ pub enum Token {
     Exec,
     Dash,
     Colon,
+    DoubleColon,
     Comma,
     Dot,
+    DotDot,   // start..end
+    FatArrow, // pattern => arm
     Access,
     IfKw,
     ElseKw,
     WhileKw,
+    ForKw,
+    InKw,
+    MatchKw,
+    FnKw, // fn args { body } is a function without a name
     BreakKw,
     Continuekw,
     SetKw,
     RetKw,
     UseKw,
     ExternKw,
+    AtomKw,
+    BondKw,
+    StateKw,
+    PartKw,
+    NewKw,
+    ModKw,
     EOF,
 }
+
+// a position in the source code, lines and columns start at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub line: u16,
+    pub column: u16,
+}
+
+impl Pos {
+    pub fn new(line: u16, column: u16) -> Self {
+        Self { line, column }
+    }
+}
+
+impl Default for Pos {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+// start..end range of source code covered by a token or a node, end is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Self {
+        Self { start, end }
+    }
+}
@@ -1,5 +1,6 @@
 use std::env::current_exe;
 use std::io::{self, Write};
+use std::process::exit;
 mod analysis;
 mod backend;
 mod compiler;
@@ -14,6 +15,7 @@ mod types;
 use std::path::Path;
 // use std::process::Command;
 use crate::compiler::{Backend, CSettings, CompilerConfig};
+use crate::err::{use_colour, ErrorFormat};
 use std::{env, fs, process::Command};
 #[test]
 fn test() {
@@ -22,12 +24,14 @@ fn test() {
 
     CompilerConfig::new(
         prog,
+        path.to_string(),
         Backend::C(CSettings::new(None, Vec::new())),
         true,
         "__tmp_test".to_string(),
         "TestProj".to_string(),
     )
-    .compile();
+    .compile()
+    .unwrap();
 }
 
 fn repl(is_debug: bool) {
@@ -41,15 +45,26 @@ fn repl(is_debug: bool) {
         print!(">> ");
         io::stdout().flush().unwrap();
 
+        let len = buffer.len();
         let _ = stdin.read_line(&mut buffer);
-        CompilerConfig::new(
+        let compiled = CompilerConfig::new(
             buffer.clone(),
+            String::new(),
             Backend::C(CSettings::new(None, Vec::new())),
             is_debug,
             "/tmp/covalent/repl".to_string(),
             current_exe().unwrap().to_str().unwrap().to_string(),
         )
         .compile();
+
+        if let Err(errors) = compiled {
+            for err in errors {
+                println!("{}", err.render(Some(&buffer), use_colour()));
+            }
+            // forget the line that failed
+            buffer.truncate(len);
+            continue;
+        }
         let _ = Command::new("/tmp/covalent/repl")
             .spawn()
             .expect("failed to execute repl exe")
@@ -59,7 +74,22 @@ fn repl(is_debug: bool) {
 
 fn main() {
     let mut is_debug = false;
-    let mut args = env::args();
+    let mut error_format = ErrorFormat::Human;
+
+    // flags can be anywhere, the rest are positional args
+    let mut args = env::args()
+        .filter(|arg| {
+            if let Some(format) = arg.strip_prefix("--error-format=") {
+                error_format = ErrorFormat::parse(format).unwrap_or_else(|| {
+                    println!("unknown error format {format}, expected human or json");
+                    exit(1);
+                });
+                return false;
+            }
+            true
+        })
+        .collect::<Vec<String>>()
+        .into_iter();
 
     if args.len() <= 1 {
         return repl(is_debug);
@@ -91,8 +121,9 @@ fn main() {
         .unwrap()
         .to_string();
 
-    CompilerConfig::new(
+    let compiled = CompilerConfig::new(
         prog.expect("invaild file name"),
+        file.clone(),
         Backend::C(CSettings::new(None, Vec::new())),
         is_debug,
         filename.replace(".atoms", ""),
@@ -103,4 +134,19 @@ fn main() {
             .to_string(),
     )
     .compile();
+
+    if let Err(errors) = compiled {
+        if error_format == ErrorFormat::Json {
+            for err in &errors {
+                println!("{}", err.to_json());
+            }
+            exit(1);
+        }
+
+        for err in &errors {
+            err.out_error();
+        }
+        println!("could not compile {} due to {} errors", file, errors.len());
+        exit(1);
+    }
 }
@@ -1,10 +1,12 @@
 use core::panic;
 
+use crate::lexer::token::Span;
 use crate::types::{self, AtomKind, AtomType, BasicType};
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
-    Int(i32),
-    Float(f32),
+    Int(i128, BasicType),
+    Float(f64, BasicType),
+    Char(u8),
     Str(String),
     Bool(bool),
 }
@@ -13,8 +15,8 @@ impl Literal {
     pub fn get_ty(&self) -> AtomType {
         AtomType {
             kind: match self {
-                &Self::Int(_) => AtomKind::Basic(BasicType::Int),
-                &Self::Float(_) => AtomKind::Basic(BasicType::Float),
+                &Self::Int(_, ty) | &Self::Float(_, ty) => AtomKind::Basic(ty),
+                &Self::Char(_) => AtomKind::Basic(BasicType::Char),
                 &Self::Str(_) => AtomKind::Atom(types::Str.clone()),
                 &Self::Bool(_) => AtomKind::Basic(BasicType::Bool),
             },
@@ -23,20 +25,48 @@ impl Literal {
     }
 }
 
-pub fn get_operator_level(op: &str) -> u8 {
+pub fn is_unary_operator(op: &str) -> bool {
+    matches!(op, "-" | "!" | "~")
+}
+
+// the binary operator a compound assignment applies, += applies +
+pub fn compound_operator(op: &str) -> Option<&str> {
+    let binary = op.strip_suffix('=')?;
+    match binary {
+        "+" | "-" | "*" | "/" | "%" | "&" | "|" | "^" | "<<" | ">>" => Some(binary),
+        _ => None,
+    }
+}
+
+// comparisons that can be chained, a < b <= c is a < b && b <= c
+pub fn is_comparison(op: &str) -> bool {
+    matches!(op, "<" | ">" | "<=" | ">=")
+}
+
+// None if op is not a binary operator
+pub fn get_operator_level(op: &str) -> Option<u8> {
     match op {
-        "&&" | "||" => 1,
-        "==" => 2,
-        "<" | ">" | ">=" | "<=" => 3,
-        "+" | "-" => 4,
-        "*" | "/" | "%" => 5,
-        _ => todo!(),
+        "&&" | "||" => Some(1),
+        "==" | "!=" => Some(2),
+        "<" | ">" | ">=" | "<=" => Some(3),
+        // bitwise operators bind tighter than comparisons, a & 1 == 0 is (a & 1) == 0
+        "|" => Some(4),
+        "^" => Some(5),
+        "&" => Some(6),
+        "<<" | ">>" => Some(7),
+        "+" | "-" => Some(8),
+        "*" | "/" | "%" => Some(9),
+        _ => None,
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
-    Use(String),
+    Use {
+        module: String,    // path of the used file or mod
+        file: bool,        // use "file.atoms" instead of use Mod
+        names: Vec<Ident>, // names to bring into scope, empty brings every name
+    },
     Literal(Literal),
     ListExpr(Vec<Node>),
 
@@ -46,15 +76,22 @@ pub enum Expr {
         right: Box<Node>,
     },
 
+    UnaryExpr {
+        op: String, // - ! or ~, not is lexed as !
+        operand: Box<Node>,
+    },
+
     Ident(Ident),
     VarDeclare {
         name: Ident,
         val: Box<Node>,
+        mutable: bool, // false if declared with part
     },
 
     VarAssign {
         name: Box<Node>,
         val: Box<Node>,
+        op: Option<String>, // the operator of a compound assignment, + for +=
     },
     // fn declare ast is genereated in parser.functions
     FnCall {
@@ -80,6 +117,31 @@ pub enum Expr {
         params: Vec<Ident>,
     },
 
+    // fn args { body }, captures are the variables around it the body uses, found by the analyzer
+    Lambda {
+        params: Vec<Ident>,
+        body: Vec<Node>,
+        captures: Vec<Ident>,
+    },
+
+    // calls a function value, a lambda or a variable holding one
+    Invoke {
+        callee: Box<Node>,
+        args: Vec<Node>,
+    },
+
+    // atom declare ast is generated in parser.atoms, this is the analyzed atom
+    Atom {
+        name: String,
+        fields: Vec<Ident>,
+    },
+
+    // new Atom: args, analyzed into an allocation passed as self to the atom's new method
+    NewExpr {
+        atom: Box<Node>,
+        args: Vec<Node>,
+    },
+
     IfExpr {
         condition: Box<Node>,
         body: Vec<Node>,
@@ -89,6 +151,32 @@ pub enum Expr {
     WhileExpr {
         condition: Box<Node>,
         body: Vec<Node>,
+        label: Option<String>, // while@name names the loop for break@name
+    },
+
+    // for var in iter { body }, iter is a RangeExpr or a List
+    ForExpr {
+        var: Ident,
+        iter: Box<Node>,
+        body: Vec<Node>,
+        label: Option<String>,
+    },
+
+    // leaves the loop named by the label or the innermost one
+    Break(Option<String>),
+    // skips to the next iteration of the loop named by the label or the innermost one
+    Continue(Option<String>),
+
+    // the first arm whose pattern fits the value runs
+    MatchExpr {
+        value: Box<Node>,
+        arms: Vec<(Pattern, Node)>,
+    },
+
+    // start..end, only the head of a for loop has one
+    RangeExpr {
+        start: Box<Node>,
+        end: Box<Node>,
     },
 
     MemberExpr {
@@ -104,56 +192,107 @@ pub enum Expr {
         parent: Box<Node>,
         spec: Vec<Node>,
     },
+    // Fn(int) -> int, the type of a function taken as an argument
+    FnType {
+        params: Vec<Node>,
+        ret: Option<Box<Node>>, // none gives nothing
+    },
 
     Discard(Box<Node>),
     Block(Vec<Node>),
-    PosInfo(String, u16, u16), // debugging
     RetExpr(Box<Node>),
     As(Box<Node>),
+    Error, // placeholder for a node that failed
+}
+
+// what an arm of a match compares the value with
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Literal(Literal, Span),
+    Range(Literal, Literal, Span), // start..end, the end is left out like in a for loop
+    Bind(Ident), // x@type fits a value of that type, x alone fits any, the arm names it x
+    Wildcard(Span), // _ fits any value
+}
+
+impl Pattern {
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Literal(_, span) | Pattern::Range(_, _, span) | Pattern::Wildcard(span) => {
+                *span
+            }
+            Pattern::Bind(id) => id.span(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Node {
     pub expr: Expr,
     pub ty: AtomType,
+    pub span: Span,
 }
 
-pub fn untyped(expr: Expr) -> Node {
+impl Node {
+    // evaluating it twice gives the same value and does nothing else
+    pub fn is_pure(&self) -> bool {
+        match &self.expr {
+            Expr::Ident(_) | Expr::Literal(_) => true,
+            Expr::MemberExpr { parent, .. }
+            | Expr::UnaryExpr {
+                operand: parent, ..
+            } => parent.is_pure(),
+            _ => false,
+        }
+    }
+}
+
+pub fn untyped(expr: Expr, span: Span) -> Node {
     Node {
         expr,
         ty: AtomType {
             kind: AtomKind::Unknown,
             details: None,
         },
+        span,
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Ident {
-    Tagged(Box<Node>, String),
-    Typed(AtomType, String),
-    UnTagged(String),
+    Tagged(Box<Node>, String, Span),
+    Typed(Box<AtomType>, String, Span),
+    UnTagged(String, Span),
 }
 
 impl Ident {
     pub fn val(&self) -> &String {
         match self {
-            Ident::Tagged(_, ref val) | Ident::UnTagged(ref val) | Ident::Typed(_, ref val) => val,
+            Ident::Tagged(_, ref val, _)
+            | Ident::UnTagged(ref val, _)
+            | Ident::Typed(_, ref val, _) => val,
         }
     }
 
     pub fn val_mut(&mut self) -> &mut String {
         match self {
-            Ident::Tagged(_, ref mut val)
-            | Ident::UnTagged(ref mut val)
-            | Ident::Typed(_, ref mut val) => val,
+            Ident::Tagged(_, ref mut val, _)
+            | Ident::UnTagged(ref mut val, _)
+            | Ident::Typed(_, ref mut val, _) => val,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Ident::Tagged(_, _, span) | Ident::UnTagged(_, span) | Ident::Typed(_, _, span) => {
+                *span
+            }
         }
     }
 
     pub fn tuple(self) -> (AtomType, String) {
         match self {
-            Ident::Typed(ty, val) => (ty, val),
-            Ident::UnTagged(val) => (
+            Ident::Typed(ty, val, _) => (*ty, val),
+            Ident::UnTagged(val, _) => (
                 AtomType {
                     kind: AtomKind::Any,
                     details: None,
@@ -166,8 +305,8 @@ impl Ident {
 
     pub fn ty(&self) -> &AtomType {
         match self {
-            Ident::Typed(ref ty, _) => ty,
-            Ident::UnTagged(_) => &AtomType {
+            Ident::Typed(ref ty, _, _) => ty,
+            Ident::UnTagged(_, _) => &AtomType {
                 kind: AtomKind::Any,
                 details: None,
             },
@@ -181,7 +320,24 @@ pub struct Blueprint {
     pub name: Ident,
     pub args: Vec<Ident>,
     pub body: Vec<Node>,
-    // pub line: u16,
-    // pub column: u16,
-    // pub width: u16,
+    pub span: Span,
+    pub file: String, // file the blueprint was declared in
+}
+
+// a mod block or a used file, its members are declared as {path}::{name}
+#[derive(Debug, Clone)]
+pub struct ModDef {
+    pub path: String,
+    pub members: Vec<String>,
+    pub span: Span,
+}
+
+// methods are pushed to parser.functions as blueprints named {atom}dot{method}
+#[derive(Debug, Clone)]
+pub struct AtomDef {
+    pub name: String,
+    pub fields: Vec<Ident>, // always tagged with the field type
+    pub parts: Vec<String>, // fields declared with part
+    pub bond: bool,         // declared with bond instead of atom
+    pub span: Span,
 }
@@ -1,8 +1,8 @@
 pub mod ast;
 pub mod parse;
-use self::ast::{Blueprint, Ident};
+use self::ast::{AtomDef, Blueprint, Ident, ModDef};
 use crate::err::{ATErr, ErrKind};
-use crate::lexer::token::Token;
+use crate::lexer::token::{Pos, Span, Token};
 use crate::lexer::Lexer;
 use crate::scope::Scope;
 use ast::Node;
@@ -10,41 +10,105 @@ use ast::Node;
 #[derive(Debug, Clone)]
 pub struct Parser {
     lexer: Lexer,
-    line: u16,
-    column: u16,
+    span: Span,    // span of the current token
+    prev_end: Pos, // where the last eaten token ended
     current_tok: Option<Token>,
     pub functions: Vec<Blueprint>,
+    pub atoms: Vec<AtomDef>,
+    pub mods: Vec<ModDef>,
+    module: String, // path of the mod being parsed, empty outside of any mod
     current_scope: Scope,
+    chains: usize, // temporaries made for chained comparisons so far
     pub errors: Vec<ATErr>,
 }
 
 impl Parser {
-    pub fn new(code: String) -> Self {
+    pub fn new(code: String, file: String) -> Self {
         Self {
-            lexer: Lexer::new(code),
-            line: 1,
-            column: 0,
+            lexer: Lexer::new(code, file),
+            span: Span::default(),
+            prev_end: Pos::default(),
             current_tok: None,
             functions: vec![],
+            atoms: vec![],
+            mods: vec![],
+            module: String::new(),
             current_scope: Scope::Top,
+            chains: 0,
             errors: Vec::new(),
         }
     }
 
+    // parses the whole file as the mod named module
+    pub fn in_module(mut self, module: &str) -> Self {
+        self.module = module.to_string();
+        self.mods.push(ModDef {
+            path: self.module.clone(),
+            members: Vec::new(),
+            span: Span::default(),
+        });
+        self
+    }
+
+    // the full name of a function or atom declared in the current mod
+    fn member(&mut self, name: &str) -> String {
+        if self.module.is_empty() {
+            return name.to_string();
+        }
+
+        let path = self.module.clone();
+        // overloads share one name
+        match self.mods.iter_mut().find(|module| module.path == path) {
+            Some(module) if !module.members.iter().any(|member| member == name) => {
+                module.members.push(name.to_string())
+            }
+            _ => (),
+        }
+        format!("{}::{}", path, name)
+    }
+
     pub fn err(&mut self, kind: ErrKind, msg: String) {
-        let err = ATErr {
-            kind,
-            msg,
-            line: self.line,
-            column: self.column,
-        };
-        self.errors.push(err.clone());
-        err.out_error();
+        self.err_at(self.span, kind, msg);
     }
 
-    pub fn push_function(&mut self, name: Ident, args: Vec<Ident>, body: Vec<Node>) {
-        self.functions.push(Blueprint { name, args, body });
+    pub fn err_at(&mut self, span: Span, kind: ErrKind, msg: String) {
+        self.report(ATErr::new(kind, msg, span));
     }
+
+    // for errors that need labels or notes
+    pub fn report(&mut self, err: ATErr) {
+        self.errors.push(err.in_file(&self.lexer.file));
+    }
+
+    // takes every error found by the lexer and the parser, in the order they appear in the code
+    pub fn errors(&mut self) -> Vec<ATErr> {
+        let mut errors: Vec<ATErr> = self.lexer.errors.drain(..).collect();
+        errors.append(&mut self.errors);
+        errors.sort_by_key(|err| err.span.start);
+        errors
+    }
+
+    pub fn push_function(&mut self, name: Ident, args: Vec<Ident>, body: Vec<Node>, span: Span) {
+        self.functions.push(Blueprint {
+            name,
+            args,
+            body,
+            span,
+            file: self.lexer.file.clone(),
+        });
+    }
+
+    // where the current token starts, used as the start of a node span
+    fn start(&mut self) -> Pos {
+        self.current();
+        self.span.start
+    }
+
+    // span from start until the end of the last eaten token
+    fn span_from(&self, start: Pos) -> Span {
+        Span::new(start, self.prev_end.max(start))
+    }
+
     fn current(&mut self) -> Token {
         if self.current_tok.is_none() {
             self.next();
@@ -52,19 +116,76 @@ impl Parser {
         self.current_tok.clone().unwrap()
     }
     fn next(&mut self) -> Token {
+        self.prev_end = self.span.end;
         let next = self.lexer.tokenize();
+        self.span = self.lexer.span();
         self.current_tok = Some(next.clone());
         next
     }
+    // true if the current token is the first one on its line
+    fn starts_line(&self) -> bool {
+        self.span.start.line > self.prev_end.line
+    }
+
+    // skips tokens after a parse error until parsing can safely continue
+    // stops before the `}` closing the current body or a keyword starting a new line
+    fn recover(&mut self) {
+        let mut depth = 0;
+        loop {
+            match self.current() {
+                Token::EOF => break,
+                Token::RightBracket if depth == 0 => break,
+                Token::RightBracket => depth -= 1,
+                Token::LeftBracket => depth += 1,
+                Token::SetKw
+                | Token::IfKw
+                | Token::WhileKw
+                | Token::ForKw
+                | Token::MatchKw
+                | Token::FnKw
+                | Token::BreakKw
+                | Token::Continuekw
+                | Token::RetKw
+                | Token::UseKw
+                | Token::ExternKw
+                | Token::AtomKw
+                | Token::BondKw
+                | Token::ModKw
+                | Token::StateKw
+                | Token::PartKw
+                    if depth == 0 && self.starts_line() =>
+                {
+                    break
+                }
+                _ => (),
+            }
+            self.next();
+        }
+    }
+
     pub fn except(&mut self, tok: Token) -> Token {
         if self.current() != tok {
             let t = self.current();
-            self.next();
-
+            // already reported by the lexer, the token after it may still be the one excepted
+            if let Token::Err(_) = t {
+                if self.next() == tok {
+                    return self.next();
+                }
+                return t;
+            }
+            // every body left open ends at the end of the file, it is only reported once
+            if t == Token::EOF && self.errors.iter().any(|err| err.span == self.span) {
+                return Token::Err("unexcepted token".to_string());
+            }
             self.err(
                 ErrKind::UnexceptedTokenE,
                 format!("unexcepted token [{:?}] excepted [{:?}]", t, tok),
             );
+            // the } closing an interpolation is left for the str to go on after it
+            if t != Token::InterpEnd {
+                self.next();
+            }
+
             Token::Err("unexcepted token".to_string())
         } else {
             self.next()
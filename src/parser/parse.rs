@@ -2,19 +2,20 @@ use crate::scope::Scope;
 
 use super::ast::*;
 use super::Parser;
-use crate::err::ErrKind;
+use crate::err::{ATErr, ErrKind};
 
-use crate::lexer::token::Token;
+use crate::lexer::token::{Pos, Span, Token};
 
-use crate::types::{AtomKind, AtomType};
+use crate::types::{self, AtomKind, AtomType};
 macro_rules! untyped {
-    ($expr: expr) => {
+    ($self: ident, $start: expr, $expr: expr) => {
         Ok(Node {
             expr: $expr,
             ty: AtomType {
                 kind: AtomKind::Unknown,
                 details: None,
             },
+            span: $self.span_from($start),
         })
     };
 }
@@ -22,7 +23,9 @@ macro_rules! untyped {
 pub trait Parse {
     fn parse_prog(&mut self) -> Vec<Node>;
     fn parse_level(&mut self, level: u8) -> Result<Node, ()>;
+    fn hoist(&mut self, operand: &mut Node, hoisted: &mut Vec<Node>) -> Node;
 
+    fn parse_unary(&mut self) -> Result<Node, ()>;
     fn parse_index(&mut self) -> Result<Node, ()>;
     fn parse_spec(&mut self) -> Result<Node, ()>;
     fn parse_call_fn(&mut self) -> Result<Node, ()>;
@@ -34,14 +37,29 @@ pub trait Parse {
 
     fn parse_extern(&mut self) -> Result<Node, ()>;
     fn parse_declare(&mut self) -> Result<Node, ()>;
-    fn parse_declare_fn(&mut self, id: Ident) -> Result<Node, ()>;
+    fn parse_declare_fn(&mut self, id: Ident, start: Pos) -> Result<Node, ()>;
+    fn parse_lambda(&mut self) -> Result<Node, ()>;
+
+    fn parse_atom(&mut self) -> Result<Node, ()>;
+    fn parse_field(&mut self) -> Result<Ident, ()>;
+    fn parse_method(&mut self, atom: &str) -> Result<Node, ()>;
+    fn parse_new(&mut self) -> Result<Node, ()>;
+
+    fn parse_mod(&mut self) -> Result<Node, ()>;
+    fn parse_use(&mut self) -> Result<Node, ()>;
 
     fn parse_if_expr(&mut self) -> Result<Node, ()>;
     fn parse_while_expr(&mut self) -> Result<Node, ()>;
+    fn parse_for_expr(&mut self) -> Result<Node, ()>;
+    fn parse_match_expr(&mut self) -> Result<Node, ()>;
+    fn parse_pattern(&mut self) -> Result<Pattern, ()>;
+    fn parse_label(&mut self) -> Result<Option<String>, ()>;
+    fn parse_jump(&mut self) -> Result<Node, ()>;
     fn parse_ret_expr(&mut self) -> Result<Node, ()>;
 
     fn parse_body(&mut self) -> Vec<Node>;
     fn parse_list(&mut self) -> Result<Vec<Node>, ()>;
+    fn parse_interp(&mut self) -> Result<Node, ()>;
 }
 
 impl Parse for Parser {
@@ -49,15 +67,20 @@ impl Parse for Parser {
         let mut body = Vec::new();
         while self.current() != Token::EOF {
             self.current_scope = Scope::Top;
+            let start = self.start();
             let expr = self.parse_level(0);
             if expr.is_ok() {
                 let mut expr = expr.unwrap();
 
                 if !self.current_scope.is_used() {
-                    expr = untyped(Expr::Discard(Box::new(expr)));
+                    let span = expr.span;
+                    expr = untyped(Expr::Discard(Box::new(expr)), span);
                 }
 
                 body.push(expr);
+            } else {
+                self.recover();
+                body.push(untyped(Expr::Error, self.span_from(start)));
             }
         }
 
@@ -65,25 +88,43 @@ impl Parse for Parser {
     }
 
     fn parse_level(&mut self, level: u8) -> Result<Node, ()> {
-        let mut left = self.parse_index()?;
+        let start = self.start();
+        let mut left = self.parse_unary()?;
         let mut right;
+        // right side of the comparison just parsed, the left side of a chained one
+        let mut compared: Option<Node> = None;
+        // temporaries holding the operands a chained comparison would evaluate twice
+        let mut hoisted: Vec<Node> = vec![];
 
         loop {
             // 5 (2*) 5 nothing (1+) 5
             if let Token::Operator(c) = self.current() {
-                if c == "=" {
+                let op = compound_operator(c.as_str());
+                if c == "=" || op.is_some() {
+                    let op = op.map(|op| op.to_string());
                     self.next();
                     self.current_scope = Scope::Value;
                     let right = self.parse_level(0)?;
 
-                    left = untyped(Expr::VarAssign {
-                        name: Box::new(left),
-                        val: Box::new(right),
-                    });
+                    left = untyped(
+                        Expr::VarAssign {
+                            name: Box::new(left),
+                            val: Box::new(right),
+                            op,
+                        },
+                        self.span_from(start),
+                    );
                     break;
                 }
 
-                let current_op_level = get_operator_level(c.as_str());
+                let current_op_level = match get_operator_level(c.as_str()) {
+                    Some(level) => level,
+                    None => {
+                        self.err(ErrKind::UnexceptedTokenE, format!("unknown operator {}", c));
+                        self.next();
+                        return Err(());
+                    }
+                };
                 if current_op_level < level {
                     break;
                 }
@@ -91,20 +132,106 @@ impl Parse for Parser {
                 self.next();
                 right = self.parse_level(current_op_level + 1)?;
 
-                left = untyped(Expr::BinaryExpr {
-                    op: c,
-                    left: Box::new(left),
-                    right: Box::new(right),
-                });
+                let comparison = is_comparison(&c);
+                let chained = compared.take().filter(|_| comparison);
+                if comparison {
+                    compared = Some(right.clone());
+                }
+
+                left = match chained {
+                    Some(mut mid) => {
+                        if !mid.is_pure() {
+                            // a < f! < b is { set t = f!; a < t && t < b }, a is stored first so it still runs first
+                            let (first, last) = match &mut left.expr {
+                                Expr::BinaryExpr {
+                                    op,
+                                    left: first,
+                                    right: last,
+                                } if op != "&&" => (Some(first), last),
+                                Expr::BinaryExpr { right: last, .. } => match &mut last.expr {
+                                    Expr::BinaryExpr { right: last, .. } => (None, last),
+                                    _ => unreachable!(),
+                                },
+                                _ => unreachable!(),
+                            };
+                            if let Some(first) = first.filter(|first| !first.is_pure()) {
+                                self.hoist(first, &mut hoisted);
+                            }
+                            mid = self.hoist(last, &mut hoisted);
+                        }
+                        let span = Span::new(mid.span.start, right.span.end);
+                        let next = untyped(
+                            Expr::BinaryExpr {
+                                op: c,
+                                left: Box::new(mid),
+                                right: Box::new(right),
+                            },
+                            span,
+                        );
+                        untyped(
+                            Expr::BinaryExpr {
+                                op: "&&".to_string(),
+                                left: Box::new(left),
+                                right: Box::new(next),
+                            },
+                            self.span_from(start),
+                        )
+                    }
+                    None => untyped(
+                        Expr::BinaryExpr {
+                            op: c,
+                            left: Box::new(left),
+                            right: Box::new(right),
+                        },
+                        self.span_from(start),
+                    ),
+                };
             } else {
                 break;
             }
         }
 
+        if !hoisted.is_empty() {
+            let span = left.span;
+            hoisted.push(left);
+            left = untyped(Expr::Block(hoisted), span);
+        }
         Ok(left)
     }
 
+    // moves an operand into a temporary declared in hoisted, returns the name now standing in for it
+    fn hoist(&mut self, operand: &mut Node, hoisted: &mut Vec<Node>) -> Node {
+        self.chains += 1;
+        // $ is never part of an id, so it cannot shadow a variable of the program
+        let name = Ident::UnTagged(format!("$chain{}", self.chains), operand.span);
+        let temp = untyped(Expr::Ident(name.clone()), operand.span);
+        let val = std::mem::replace(operand, temp.clone());
+        hoisted.push(untyped(
+            Expr::VarDeclare {
+                name,
+                val: Box::new(val),
+                mutable: false,
+            },
+            temp.span,
+        ));
+        temp
+    }
+
+    // -x !x ~x bind tighter than any binary operator, -a.b is -(a.b)
+    fn parse_unary(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        match self.current() {
+            Token::Operator(op) if is_unary_operator(&op) => {
+                self.next();
+                let operand = Box::new(self.parse_unary()?);
+                untyped!(self, start, Expr::UnaryExpr { op, operand })
+            }
+            _ => self.parse_index(),
+        }
+    }
+
     fn parse_index(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         let expr = self.parse_call_fn()?;
 
         if self.current() == Token::LeftBrace {
@@ -112,48 +239,83 @@ impl Parse for Parser {
             let index = Box::new(self.parse_level(0)?);
             self.except(Token::RightBrace);
 
-            return untyped!(Expr::IndexExpr {
-                parent: Box::new(expr),
-                index,
-            });
+            return untyped!(
+                self,
+                start,
+                Expr::IndexExpr {
+                    parent: Box::new(expr),
+                    index,
+                }
+            );
         }
         Ok(expr)
     }
 
     fn parse_call_fn(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         let call = self.parse_spec()?;
         if self.current() == Token::Colon {
             self.next();
             let args = self.parse_list()?;
-            return untyped!(Expr::FnCall {
-                name: Box::new(call),
-                args,
-            });
+            return untyped!(
+                self,
+                start,
+                Expr::FnCall {
+                    name: Box::new(call),
+                    args,
+                }
+            );
         }
 
         if self.current() == Token::Exec {
             self.next();
-            return untyped!(Expr::FnCall {
-                name: Box::new(call),
-                args: Vec::new(),
-            });
+            return untyped!(
+                self,
+                start,
+                Expr::FnCall {
+                    name: Box::new(call),
+                    args: Vec::new(),
+                }
+            );
         }
 
         Ok(call)
     }
 
     fn parse_spec(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         let mut left = self.parse_member()?;
 
+        if matches!(&left.expr, Expr::Ident(Ident::UnTagged(name, _)) if name == "Fn") {
+            self.except(Token::LeftParen);
+            let params = if self.current() == Token::RightParen {
+                vec![]
+            } else {
+                self.parse_spec_list()?
+            };
+            self.except(Token::RightParen);
+
+            let ret = if self.current() == Token::Access {
+                self.next();
+                Some(Box::new(self.parse_spec()?))
+            } else {
+                None
+            };
+            return untyped!(self, start, Expr::FnType { params, ret });
+        }
+
         if self.current() == Token::LeftParen {
             self.next();
             let spec = self.parse_spec_list()?;
-
-            left = untyped(Expr::SpecExpr {
-                parent: Box::new(left),
-                spec,
-            });
             self.except(Token::RightParen);
+
+            left = untyped(
+                Expr::SpecExpr {
+                    parent: Box::new(left),
+                    spec,
+                },
+                self.span_from(start),
+            );
         }
 
         Ok(left)
@@ -172,25 +334,32 @@ impl Parse for Parser {
     }
 
     fn parse_member(&mut self) -> Result<Node, ()> {
-        let left = self.parse_expr()?;
-        if self.current() == Token::Dot {
+        let start = self.start();
+        let mut left = self.parse_expr()?;
+
+        // a.b.c is (a.b).c
+        while self.current() == Token::Dot {
             self.next();
             let right = self.parse_expr()?;
             if let Expr::Ident(id) = right.expr {
-                untyped!(Expr::MemberExpr {
-                    parent: Box::new(left),
-                    child: id.val().clone(),
-                })
+                left = untyped(
+                    Expr::MemberExpr {
+                        parent: Box::new(left),
+                        child: id.val().clone(),
+                    },
+                    self.span_from(start),
+                );
             } else {
-                self.err(
+                self.err_at(
+                    right.span,
                     ErrKind::UnexceptedTokenE,
-                    format!("expected id in member expr got {:?}", right),
+                    "expected an id after '.'".to_string(),
                 );
-                untyped!(Expr::Literal(Literal::Int(0)))
+                return untyped!(self, start, Expr::Error);
             }
-        } else {
-            Ok(left)
         }
+
+        Ok(left)
     }
 
     fn parse_list(&mut self) -> Result<Vec<Node>, ()> {
@@ -205,35 +374,178 @@ impl Parse for Parser {
         Ok(items)
     }
 
+    // "a {x} b" is "a " + x as str + " b"
+    fn parse_interp(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        let str_ty = AtomType {
+            kind: AtomKind::Atom(types::Str.clone()),
+            details: None,
+        };
+        let mut parts: Vec<Node> = Vec::new();
+
+        let mut failed = false;
+
+        loop {
+            let (text, done) = match self.current() {
+                Token::StrPart(text) => (text, false),
+                Token::Str(text) => (text, true),
+                // the rest of the str is never closed, already reported by the lexer
+                _ => return Err(()),
+            };
+            let span = self.span;
+            self.next();
+            if !text.is_empty() {
+                parts.push(untyped(Expr::Literal(Literal::Str(text)), span));
+            }
+            if done {
+                break;
+            }
+
+            // the { the expression starts after
+            let open = Pos::new(span.end.line, span.end.column - 1);
+            if self.current() == Token::InterpEnd {
+                let inner = Span::new(open, self.span.end);
+                self.report(
+                    ATErr::new(
+                        ErrKind::UnexceptedTokenE,
+                        "empty interpolation in str".to_string(),
+                        inner,
+                    )
+                    .note("put an expression between { and } or write \\{ for a {"),
+                );
+                self.next();
+                failed = true;
+                continue;
+            }
+
+            let reported = self.errors.len();
+            self.current_scope = Scope::Value;
+            let expr = self.parse_level(0);
+            if let (Ok(expr), Token::InterpEnd) = (&expr, self.current()) {
+                let span = expr.span;
+                parts.push(Node {
+                    expr: Expr::As(Box::new(expr.clone())),
+                    ty: str_ty.clone(),
+                    span,
+                });
+                self.next();
+                continue;
+            }
+
+            // skip the rest of the expression, strs interpolated in it included
+            let mut depth = 0;
+            loop {
+                match self.current() {
+                    Token::EOF => return Err(()),
+                    Token::InterpEnd if depth == 0 => break,
+                    Token::InterpEnd => depth -= 1,
+                    Token::StrPart(_) => depth += 1,
+                    _ => (),
+                }
+                self.next();
+            }
+            let inner = Span::new(open, self.span.end);
+            self.next();
+            failed = true;
+
+            // whatever the expression ran into is reported as a whole
+            self.errors.truncate(reported);
+            self.report(
+                ATErr::new(
+                    ErrKind::UnexceptedTokenE,
+                    "invaild expression in interpolation".to_string(),
+                    self.span_from(start),
+                )
+                .label(inner, "this is not a single expression"),
+            );
+        }
+
+        if failed {
+            return Err(());
+        }
+
+        let mut parts = parts.into_iter();
+        let mut res = match parts.next() {
+            Some(first) => first,
+            None => return untyped!(self, start, Expr::Literal(Literal::Str(String::new()))),
+        };
+        for part in parts {
+            res = untyped(
+                Expr::BinaryExpr {
+                    op: "+".to_string(),
+                    left: Box::new(res),
+                    right: Box::new(part),
+                },
+                self.span_from(start),
+            );
+        }
+        Ok(res)
+    }
+
     fn parse_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         let tok = self.current();
         match tok {
-            Token::Int(i) => {
+            Token::Int(i, ty) => {
                 self.next();
-                untyped!(Expr::Literal(Literal::Int(i)))
+                untyped!(self, start, Expr::Literal(Literal::Int(i, ty)))
             }
-            Token::Float(f) => {
+            Token::Float(f, ty) => {
                 self.next();
-                untyped!(Expr::Literal(Literal::Float(f)))
+                untyped!(self, start, Expr::Literal(Literal::Float(f, ty)))
+            }
+            Token::Char(c) => {
+                self.next();
+                untyped!(self, start, Expr::Literal(Literal::Char(c)))
             }
             Token::Bool(val) => {
                 self.next();
-                untyped!(Expr::Literal(Literal::Bool(val)))
+                untyped!(self, start, Expr::Literal(Literal::Bool(val)))
             }
             Token::Str(s) => {
                 self.next();
-                untyped!(Expr::Literal(Literal::Str(s)))
+                untyped!(self, start, Expr::Literal(Literal::Str(s)))
             }
+            Token::StrPart(_) => self.parse_interp(),
 
-            Token::Err(_) => Err(()),
+            // already reported by the lexer
+            Token::Err(_) => {
+                self.next();
+                Err(())
+            }
+            // the expression ended before the } closing an interpolation, it is left for parse_interp
+            Token::InterpEnd => {
+                self.err(
+                    ErrKind::UnexceptedTokenE,
+                    "expected an expression before }".to_string(),
+                );
+                Err(())
+            }
 
-            Token::Ident(id) => {
+            Token::Ident(mut id) => {
                 self.next();
+                // paths into mods, Mod::name
+                while self.current() == Token::DoubleColon {
+                    if let Token::Ident(name) = self.next() {
+                        id = format!("{}::{}", id, name);
+                        self.next();
+                    } else {
+                        self.err(
+                            ErrKind::UnexceptedTokenE,
+                            "expected an id after '::'".to_string(),
+                        );
+                        return Err(());
+                    }
+                }
+
                 if self.current() == Token::Dash {
                     self.next();
-                    untyped!(Expr::Ident(Ident::Tagged(Box::new(self.parse_spec()?), id)))
+                    let tag = Box::new(self.parse_spec()?);
+                    let span = self.span_from(start);
+                    untyped!(self, start, Expr::Ident(Ident::Tagged(tag, id, span)))
                 } else {
-                    untyped!(Expr::Ident(Ident::UnTagged(id)))
+                    let span = self.span_from(start);
+                    untyped!(self, start, Expr::Ident(Ident::UnTagged(id, span)))
                 }
             }
             // Token::Tag(tag) => {
@@ -251,39 +563,41 @@ impl Parse for Parser {
                 self.next();
                 let expr = self.parse_level(0);
                 self.except(Token::RightParen);
-                expr
+                expr.map(|mut expr| {
+                    expr.span = self.span_from(start);
+                    expr
+                })
+            }
+
+            Token::LeftBracket => {
+                let body = self.parse_body();
+                untyped!(self, start, Expr::Block(body))
             }
 
             Token::LeftBrace => {
                 self.next();
                 let values = self.parse_list()?;
                 self.except(Token::RightBrace);
-                untyped!(Expr::ListExpr(values))
-            }
-            Token::UseKw => {
-                if let Token::Str(path) = self.next() {
-                    self.current_scope = Scope::Use;
-                    self.next();
-                    untyped!(Expr::Use(path))
-                } else {
-                    let tok = self.current();
-                    self.err(
-                        ErrKind::UnexceptedTokenE,
-                        format!("unexcepted token [{:#?}]", tok),
-                    );
-                    Err(())
-                }
+                untyped!(self, start, Expr::ListExpr(values))
             }
+            Token::UseKw => self.parse_use(),
+            Token::ModKw => self.parse_mod(),
             Token::ExternKw => self.parse_extern(),
 
-            Token::SetKw => self.parse_declare(),
+            Token::SetKw | Token::StateKw | Token::PartKw => self.parse_declare(),
+            Token::AtomKw | Token::BondKw => self.parse_atom(),
+            Token::NewKw => self.parse_new(),
             Token::WhileKw => self.parse_while_expr(),
+            Token::ForKw => self.parse_for_expr(),
+            Token::MatchKw => self.parse_match_expr(),
+            Token::FnKw => self.parse_lambda(),
+            Token::BreakKw | Token::Continuekw => self.parse_jump(),
             Token::IfKw => self.parse_if_expr(),
             Token::RetKw => self.parse_ret_expr(),
             _ => {
                 self.err(
                     ErrKind::UnexceptedTokenE,
-                    format!("unexcepted token [{:#?}]", tok),
+                    format!("unexcepted token [{:?}]", tok),
                 );
                 self.next();
 
@@ -294,12 +608,13 @@ impl Parse for Parser {
     }
 
     fn parse_extern(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         self.next();
 
         let name = self.parse_expr()?;
 
         if let Expr::Ident(id) = name.expr {
-            if let Ident::Tagged(_, _) = id {
+            if let Ident::Tagged(_, _, _) = id {
                 let name = id;
 
                 self.except(Token::Colon);
@@ -310,68 +625,90 @@ impl Parse for Parser {
 
                 for (i, node) in params.iter().enumerate() {
                     if let Expr::Ident(ref id) = node.expr {
-                        if let Ident::Tagged(_, _) = id {
+                        if let Ident::Tagged(_, _, _) = id {
                             id_params.push(id.clone());
                             continue;
                         }
                     }
 
-                    self.err(
+                    self.err_at(
+                        node.span,
                         ErrKind::UnexceptedTokenE,
                         format!("expected a typed id as extern param {i}"),
                     );
                 }
                 let params = id_params;
 
-                untyped!(Expr::Extern { name, params })
+                untyped!(self, start, Expr::Extern { name, params })
             } else {
-                self.err(
+                self.err_at(
+                    name.span,
                     ErrKind::UnexceptedTokenE,
                     format!("expected a typed id as extern name"),
                 );
 
-                todo!()
+                Err(())
             }
         } else {
-            self.err(
+            self.err_at(
+                name.span,
                 ErrKind::UnexceptedTokenE,
                 format!("expected an id in extern"),
             );
 
-            todo!()
+            Err(())
         }
     }
 
+    // set and state declare mutable variables, part declares one that cannot be assigned again
     fn parse_declare(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        let keyword = self.current();
         self.next();
 
         let left = self.parse_expr()?;
         self.current_scope = Scope::Value;
-        if let Expr::Ident(name) = left.expr {
+        if let Expr::Ident(mut name) = left.expr {
             if Token::Operator("=".to_string()) == self.current() {
                 self.next();
 
                 let expr = self.parse_level(0)?;
-                return untyped!(Expr::VarDeclare {
-                    name,
-                    val: Box::new(expr),
-                });
+                return untyped!(
+                    self,
+                    start,
+                    Expr::VarDeclare {
+                        name,
+                        val: Box::new(expr),
+                        mutable: keyword != Token::PartKw,
+                    }
+                );
             }
 
-            self.parse_declare_fn(name)
+            if keyword != Token::SetKw {
+                self.err(
+                    ErrKind::UnexceptedTokenE,
+                    format!(
+                        "expected = after {}, functions are declared with set",
+                        name.val()
+                    ),
+                );
+                return Err(());
+            }
+
+            let full = self.member(&name.val().clone());
+            *name.val_mut() = full;
+            self.parse_declare_fn(name, start)
         } else {
-            self.err(
+            self.err_at(
+                left.span,
                 ErrKind::UnexceptedTokenE,
-                format!(
-                    "unexcept token in set expression [{:?}] excepted an id",
-                    left
-                ),
+                "excepted an id in set expression".to_string(),
             );
 
-            Ok(left)
+            Err(())
         }
     }
-    fn parse_declare_fn(&mut self, id: Ident) -> Result<Node, ()> {
+    fn parse_declare_fn(&mut self, id: Ident, start: Pos) -> Result<Node, ()> {
         let mut id_args: Vec<Ident> = Vec::new();
 
         if self.current() == Token::Colon {
@@ -382,24 +719,372 @@ impl Parse for Parser {
                 if let Expr::Ident(id) = arg.expr {
                     id_args.push(id);
                 } else {
-                    self.err(
+                    self.err_at(
+                        arg.span,
                         ErrKind::UnexceptedArgs,
                         "excepted an id for arg".to_string(),
                     );
-                    return self.parse_level(0);
+                    return Err(());
                 }
             }
+        } else if let Token::Err(_) = self.except(Token::Exec) {
+            return Err(());
+        }
+        let body = self.parse_body();
+
+        let span = self.span_from(start);
+        self.push_function(id, id_args, body, span);
+        self.current_scope = Scope::Value;
+        // functions are collected in self.functions, leave an empty block in their place
+        untyped!(self, start, Expr::Block(Vec::new()))
+    }
+
+    // fn x, y { body } or fn! { body }, args are declared like the args of a function
+    fn parse_lambda(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        // the body resets the scope, a lambda is used wherever it was
+        let scope = self.current_scope.clone();
+        let mut params: Vec<Ident> = Vec::new();
+
+        if self.next() == Token::Exec {
+            self.next();
         } else {
-            self.except(Token::Exec);
+            for arg in self.parse_list()? {
+                if let Expr::Ident(id) = arg.expr {
+                    params.push(id);
+                } else {
+                    self.err_at(
+                        arg.span,
+                        ErrKind::UnexceptedArgs,
+                        "excepted an id for arg".to_string(),
+                    );
+                    return Err(());
+                }
+            }
         }
         let body = self.parse_body();
 
-        self.push_function(id.clone(), id_args, body);
+        self.current_scope = scope;
+        untyped!(
+            self,
+            start,
+            Expr::Lambda {
+                params,
+                body,
+                captures: Vec::new(),
+            }
+        )
+    }
+
+    // atoms and bonds share the same syntax
+    fn parse_atom(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        let bond = self.current() == Token::BondKw;
+        self.next();
+
+        let name = if let Token::Ident(name) = self.current() {
+            self.next();
+            self.member(&name)
+        } else {
+            self.err(
+                ErrKind::UnexceptedTokenE,
+                format!("expected a name for {}", if bond { "bond" } else { "atom" }),
+            );
+            return Err(());
+        };
+
+        let mut fields = Vec::new();
+        let mut parts = Vec::new();
+
+        self.except(Token::LeftBracket);
+        while self.current() != Token::RightBracket && self.current() != Token::EOF {
+            let item = match self.current() {
+                Token::StateKw => self.parse_field().map(|field| fields.push(field)),
+                Token::PartKw => self.parse_field().map(|field| {
+                    parts.push(field.val().clone());
+                    fields.push(field);
+                }),
+                Token::SetKw => self.parse_method(&name).map(|_| ()),
+                // already reported by the lexer
+                Token::Err(_) => {
+                    self.next();
+                    Err(())
+                }
+                tok => {
+                    self.err(
+                        ErrKind::UnexceptedTokenE,
+                        format!(
+                            "unexcepted token [{:?}] in {}, excepted a field or a method",
+                            tok, name
+                        ),
+                    );
+                    self.next();
+                    Err(())
+                }
+            };
+
+            if item.is_err() {
+                self.recover();
+            }
+        }
+        self.except(Token::RightBracket);
+
+        let span = self.span_from(start);
+        self.atoms.push(AtomDef {
+            name,
+            fields,
+            parts,
+            bond,
+            span,
+        });
+        self.current_scope = Scope::Value;
+        // atoms are collected in self.atoms, leave an empty block in their place
+        untyped!(self, start, Expr::Block(Vec::new()))
+    }
+
+    fn parse_field(&mut self) -> Result<Ident, ()> {
+        self.next();
+
+        let field = self.parse_expr()?;
+        if let Expr::Ident(name @ Ident::Tagged(_, _, _)) = field.expr {
+            Ok(name)
+        } else {
+            self.err_at(
+                field.span,
+                ErrKind::UnexceptedTokenE,
+                "expected a typed id as field, for example `state x@int`".to_string(),
+            );
+            Err(())
+        }
+    }
+
+    // methods are blueprints named {atom}dot{method} taking the atom as self first
+    fn parse_method(&mut self, atom: &str) -> Result<Node, ()> {
+        let start = self.start();
+        self.next();
+
+        let name = if self.current() == Token::NewKw {
+            let span = self.span;
+            self.next();
+            Ident::UnTagged("new".to_string(), span)
+        } else {
+            let name = self.parse_expr()?;
+            if let Expr::Ident(name) = name.expr {
+                name
+            } else {
+                self.err_at(
+                    name.span,
+                    ErrKind::UnexceptedTokenE,
+                    "excepted an id for method name".to_string(),
+                );
+                return Err(());
+            }
+        };
+
+        if self.current() == Token::Operator("=".to_string()) {
+            self.err(
+                ErrKind::UnexceptedTokenE,
+                "atoms can only declare fields using state or part".to_string(),
+            );
+            return Err(());
+        }
+
+        let method = name.val().clone();
+        let node = self.parse_declare_fn(name, start)?;
+
+        let blueprint = self.functions.last_mut().unwrap();
+        *blueprint.name.val_mut() = format!("{}dot{}", atom, method);
+
+        let span = blueprint.name.span();
+        let atom_tag = untyped(Expr::Ident(Ident::UnTagged(atom.to_string(), span)), span);
+        blueprint.args.insert(
+            0,
+            Ident::Tagged(Box::new(atom_tag), "self".to_string(), span),
+        );
+
+        // new gives back the atom it initialized
+        if method == "new" {
+            let this = untyped(Expr::Ident(Ident::UnTagged("self".to_string(), span)), span);
+            blueprint
+                .body
+                .push(untyped(Expr::RetExpr(Box::new(this)), span));
+        }
+
+        Ok(node)
+    }
+
+    // mods group functions, atoms and other mods under {mod}::{name}
+    fn parse_mod(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        self.next();
+
+        let name = if let Token::Ident(name) = self.current() {
+            self.next();
+            name
+        } else {
+            self.err(
+                ErrKind::UnexceptedTokenE,
+                "expected a name for mod".to_string(),
+            );
+            return Err(());
+        };
+
+        let path = if self.module.is_empty() {
+            name
+        } else {
+            format!("{}::{}", self.module, name)
+        };
+        let span = self.span_from(start);
+        if self.mods.iter().any(|module| module.path == path) {
+            self.err_at(
+                span,
+                ErrKind::VarAlreadyDeclared,
+                format!("mod {} is already declared", path),
+            );
+        } else {
+            self.mods.push(ModDef {
+                path: path.clone(),
+                members: Vec::new(),
+                span,
+            });
+        }
+        let parent = std::mem::replace(&mut self.module, path);
+
+        self.except(Token::LeftBracket);
+        while self.current() != Token::RightBracket && self.current() != Token::EOF {
+            let item = match self.current() {
+                Token::SetKw => self.parse_declare().and_then(|node| match node.expr {
+                    Expr::VarDeclare { .. } => {
+                        self.err_at(
+                            node.span,
+                            ErrKind::UnexceptedTokenE,
+                            "mods can only declare functions, atoms, bonds and mods".to_string(),
+                        );
+                        Err(())
+                    }
+                    _ => Ok(()),
+                }),
+                Token::AtomKw | Token::BondKw => self.parse_atom().map(|_| ()),
+                Token::ModKw => self.parse_mod().map(|_| ()),
+                // already reported by the lexer
+                Token::Err(_) => {
+                    self.next();
+                    Err(())
+                }
+                tok => {
+                    self.err(
+                        ErrKind::UnexceptedTokenE,
+                        format!(
+                            "unexcepted token [{:?}] in mod {}, excepted a function, an atom, a bond or a mod",
+                            tok, self.module
+                        ),
+                    );
+                    self.next();
+                    Err(())
+                }
+            };
+
+            if item.is_err() {
+                self.recover();
+            }
+        }
+        self.except(Token::RightBracket);
+
+        self.module = parent;
         self.current_scope = Scope::Value;
-        untyped!(Expr::PosInfo(id.val().clone(), self.line, self.column))
+        // members are collected like any other function or atom, leave an empty block in their place
+        untyped!(self, start, Expr::Block(Vec::new()))
+    }
+
+    // use "file.atoms" or use Mod, `: names` only brings the names listed into scope
+    fn parse_use(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        self.next();
+
+        let (module, file) = match self.current() {
+            Token::Str(path) => {
+                self.next();
+                (path, true)
+            }
+            Token::Ident(_) => {
+                let module = self.parse_expr()?;
+                if let Expr::Ident(Ident::UnTagged(path, _)) = module.expr {
+                    (path, false)
+                } else {
+                    self.err_at(
+                        module.span,
+                        ErrKind::UnexceptedTokenE,
+                        "expected a mod to use".to_string(),
+                    );
+                    return Err(());
+                }
+            }
+            // already reported by the lexer
+            Token::Err(_) => return Err(()),
+            tok => {
+                self.err(
+                    ErrKind::UnexceptedTokenE,
+                    format!(
+                        "unexcepted token [{:?}], excepted a file or a mod to use",
+                        tok
+                    ),
+                );
+                return Err(());
+            }
+        };
+
+        let mut names = Vec::new();
+        if self.current() == Token::Colon {
+            self.next();
+            for name in self.parse_list()? {
+                if let Expr::Ident(id @ Ident::UnTagged(_, _)) = name.expr {
+                    names.push(id);
+                } else {
+                    self.err_at(
+                        name.span,
+                        ErrKind::UnexceptedTokenE,
+                        "expected a name to use".to_string(),
+                    );
+                    return Err(());
+                }
+            }
+        }
+
+        self.current_scope = Scope::Use;
+        untyped!(
+            self,
+            start,
+            Expr::Use {
+                module,
+                file,
+                names
+            }
+        )
+    }
+
+    fn parse_new(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        self.next();
+
+        let atom = Box::new(self.parse_spec()?);
+
+        let args = if self.current() == Token::Colon {
+            self.next();
+            self.parse_list()?
+        } else {
+            if self.current() == Token::Exec {
+                self.next();
+            }
+            Vec::new()
+        };
+
+        untyped!(self, start, Expr::NewExpr { atom, args })
     }
 
     fn parse_if_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        // an if on its own line is still discarded, its value is only used where one is wanted
+        let scope = self.current_scope.clone();
         self.next(); // remove if
         self.current_scope = Scope::Value;
         let condition = self.parse_level(0)?;
@@ -411,26 +1096,193 @@ impl Parse for Parser {
             if self.current() == Token::IfKw {
                 alt = Some(Box::new(self.parse_if_expr()?));
             } else {
-                alt = Some(Box::new(untyped(Expr::Block(self.parse_body()))));
+                let alt_start = self.start();
+                let body = self.parse_body();
+                alt = Some(Box::new(untyped(
+                    Expr::Block(body),
+                    self.span_from(alt_start),
+                )));
             }
         }
 
-        untyped!(Expr::IfExpr {
-            condition: Box::new(condition),
-            body,
-            alt,
-        })
+        self.current_scope = scope;
+        untyped!(
+            self,
+            start,
+            Expr::IfExpr {
+                condition: Box::new(condition),
+                body,
+                alt,
+            }
+        )
     }
     fn parse_while_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         self.next();
+        let label = self.parse_label()?;
         self.current_scope = Scope::Value;
         let condition = self.parse_level(0)?;
         let body = self.parse_body();
 
-        untyped!(Expr::WhileExpr {
-            condition: Box::new(condition),
-            body,
-        })
+        untyped!(
+            self,
+            start,
+            Expr::WhileExpr {
+                condition: Box::new(condition),
+                body,
+                label,
+            }
+        )
+    }
+
+    // @name after a loop keyword or a break or continue
+    fn parse_label(&mut self) -> Result<Option<String>, ()> {
+        if self.current() != Token::Dash {
+            return Ok(None);
+        }
+        self.next();
+
+        if let Token::Ident(label) = self.current() {
+            self.next();
+            Ok(Some(label))
+        } else {
+            self.err(
+                ErrKind::UnexceptedTokenE,
+                "expected a loop name after @".to_string(),
+            );
+            Err(())
+        }
+    }
+
+    fn parse_jump(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        let kw = self.current();
+        self.next();
+        self.current_scope = Scope::Value;
+        let label = self.parse_label()?;
+
+        let expr = if kw == Token::BreakKw {
+            Expr::Break(label)
+        } else {
+            Expr::Continue(label)
+        };
+        untyped!(self, start, expr)
+    }
+
+    // for i in 0..10 { } or for item in list { }
+    fn parse_for_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        self.next();
+        let label = self.parse_label()?;
+        self.current_scope = Scope::Value;
+
+        let var = if let Token::Ident(name) = self.current() {
+            let span = self.span;
+            self.next();
+            Ident::UnTagged(name, span)
+        } else {
+            self.err(
+                ErrKind::UnexceptedTokenE,
+                "expected a name for the loop variable".to_string(),
+            );
+            return Err(());
+        };
+        self.except(Token::InKw);
+
+        let iter_start = self.start();
+        let mut iter = self.parse_level(0)?;
+        if self.current() == Token::DotDot {
+            self.next();
+            let end = self.parse_level(0)?;
+            iter = untyped(
+                Expr::RangeExpr {
+                    start: Box::new(iter),
+                    end: Box::new(end),
+                },
+                self.span_from(iter_start),
+            );
+        }
+        let body = self.parse_body();
+
+        untyped!(
+            self,
+            start,
+            Expr::ForExpr {
+                var,
+                iter: Box::new(iter),
+                body,
+                label,
+            }
+        )
+    }
+
+    // match value { pattern => arm }, arms can be split by commas
+    fn parse_match_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
+        // like an if a match on its own line is discarded
+        let scope = self.current_scope.clone();
+        self.next();
+        self.current_scope = Scope::Value;
+        let value = self.parse_level(0)?;
+
+        let mut arms = vec![];
+        self.except(Token::LeftBracket);
+        while self.current() != Token::RightBracket && self.current() != Token::EOF {
+            let pattern = self.parse_pattern()?;
+            self.except(Token::FatArrow);
+            self.current_scope = Scope::Value;
+            let arm = self.parse_level(0)?;
+            arms.push((pattern, arm));
+
+            if self.current() == Token::Comma {
+                self.next();
+            }
+        }
+        self.except(Token::RightBracket);
+
+        self.current_scope = scope;
+        untyped!(
+            self,
+            start,
+            Expr::MatchExpr {
+                value: Box::new(value),
+                arms,
+            }
+        )
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, ()> {
+        let start = self.start();
+        if self.current() == Token::Ident("_".to_string()) {
+            self.next();
+            return Ok(Pattern::Wildcard(self.span_from(start)));
+        }
+
+        let pattern = self.parse_expr()?;
+        match pattern.expr {
+            Expr::Ident(id) => Ok(Pattern::Bind(id)),
+            Expr::Literal(first) if self.current() == Token::DotDot => {
+                self.next();
+                let end = self.parse_expr()?;
+                if let Expr::Literal(last) = end.expr {
+                    Ok(Pattern::Range(first, last, self.span_from(start)))
+                } else {
+                    self.err(
+                        ErrKind::UnexceptedTokenE,
+                        "expected a literal to end the range".to_string(),
+                    );
+                    Err(())
+                }
+            }
+            Expr::Literal(literal) => Ok(Pattern::Literal(literal, pattern.span)),
+            _ => {
+                self.err(
+                    ErrKind::UnexceptedTokenE,
+                    "expected a literal, a range, a name or _ as the pattern".to_string(),
+                );
+                Err(())
+            }
+        }
     }
 
     #[inline]
@@ -440,15 +1292,20 @@ impl Parse for Parser {
         self.except(Token::LeftBracket);
         while self.current() != Token::RightBracket && self.current() != Token::EOF {
             self.current_scope = Scope::Top;
+            let start = self.start();
             let expr = self.parse_level(0);
             if expr.is_ok() {
                 let mut expr = expr.unwrap();
 
                 if !self.current_scope.is_used() {
-                    expr = untyped(Expr::Discard(Box::new(expr)));
+                    let span = expr.span;
+                    expr = untyped(Expr::Discard(Box::new(expr)), span);
                 }
 
                 body.push(expr);
+            } else {
+                self.recover();
+                body.push(untyped(Expr::Error, self.span_from(start)));
             }
         }
         self.except(Token::RightBracket);
@@ -457,9 +1314,112 @@ impl Parse for Parser {
     }
 
     fn parse_ret_expr(&mut self) -> Result<Node, ()> {
+        let start = self.start();
         self.next();
         self.current_scope = Scope::Value;
         let expr = self.parse_level(0)?;
-        untyped!(Expr::RetExpr(Box::new(expr)))
+        untyped!(self, start, Expr::RetExpr(Box::new(expr)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the expression on the only line of code, written like (op left right)
+    fn parsed(code: &str) -> String {
+        let mut parser = Parser::new(code.to_string(), String::new());
+        let mut prog = parser.parse_prog();
+        assert!(parser.errors.is_empty(), "{} did not parse", code);
+        assert_eq!(prog.len(), 1);
+        show(&prog.remove(0))
+    }
+
+    fn show(node: &Node) -> String {
+        match &node.expr {
+            Expr::Discard(expr) => show(expr),
+            Expr::Ident(id) => id.val().clone(),
+            Expr::Literal(Literal::Int(i, _)) => i.to_string(),
+            Expr::BinaryExpr { op, left, right } => {
+                format!("({} {} {})", op, show(left), show(right))
+            }
+            Expr::FnCall { name, args } if args.is_empty() => format!("{}!", show(name)),
+            Expr::VarDeclare { name, val, .. } => format!("set {} = {}", name.val(), show(val)),
+            Expr::Block(body) => {
+                let body: Vec<String> = body.iter().map(show).collect();
+                format!("{{ {} }}", body.join("; "))
+            }
+            expr => panic!("{:?} is not shown", expr),
+        }
+    }
+
+    #[test]
+    fn comparisons_chain() {
+        assert_eq!(parsed("a < b"), "(< a b)");
+        assert_eq!(parsed("a < b < c"), "(&& (< a b) (< b c))");
+        assert_eq!(
+            parsed("0 <= i < n >= m"),
+            "(&& (&& (<= 0 i) (< i n)) (>= n m))"
+        );
+        // operators binding tighter are part of the operand, the ones binding looser are not
+        assert_eq!(parsed("a + 1 < b < c"), "(&& (< (+ a 1) b) (< b c))");
+        assert_eq!(parsed("a < b < c && d"), "(&& (&& (< a b) (< b c)) d)");
+        assert_eq!(parsed("a == b < c"), "(== a (< b c))");
+    }
+
+    #[test]
+    fn chained_operands_run_once() {
+        assert_eq!(
+            parsed("0 < f! < 10"),
+            "{ set $chain1 = f!; (&& (< 0 $chain1) (< $chain1 10)) }"
+        );
+        // the first operand is stored too so it still runs before the middle one
+        assert_eq!(
+            parsed("g! < f! < 10"),
+            "{ set $chain1 = g!; set $chain2 = f!; (&& (< $chain1 $chain2) (< $chain2 10)) }"
+        );
+        // the last operand is only evaluated by the last comparison
+        assert_eq!(
+            parsed("0 < a < f! < g!"),
+            "{ set $chain1 = f!; (&& (&& (< 0 a) (< a $chain1)) (< $chain1 g!)) }"
+        );
+    }
+
+    #[test]
+    fn interp_errors() {
+        let errors = |code: &str| {
+            let mut parser = Parser::new(code.to_string(), String::new());
+            parser.parse_prog();
+            parser
+                .errors()
+                .into_iter()
+                .map(|err| err.msg)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors("\"a{}b\""), ["empty interpolation in str"]);
+        assert_eq!(
+            errors("\"a{1 +}b\""),
+            ["invaild expression in interpolation"]
+        );
+        assert_eq!(
+            errors("\"a{1 2}b{}\""),
+            [
+                "invaild expression in interpolation",
+                "empty interpolation in str"
+            ]
+        );
+    }
+
+    #[test]
+    fn lexer_errors_are_reported_once() {
+        let errors = |code: &str| {
+            let mut parser = Parser::new(code.to_string(), String::new());
+            parser.parse_prog();
+            parser.errors().len()
+        };
+        assert_eq!(errors("set A$f = 3"), 1);
+        assert_eq!(errors("atom P {\n state x@int $\n}"), 1);
+        // the token after the bad one is still the one excepted
+        assert_eq!(errors("set f$! { ret 1 }"), 1);
     }
 }
@@ -1,14 +1,22 @@
 use core::fmt::Display;
 use indexmap::IndexMap;
-use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BasicType {
-    Int,
-    Float,
-
+    Int, // i32
+    I8,
+    I16,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Float, // f32
+    F64,
+
+    Char, // a single byte, indexing a str gives one
     Bool,
     Void,
 }
@@ -18,12 +26,113 @@ impl Display for BasicType {
         match self {
             Self::Float => write!(f, "float"),
             Self::Int => write!(f, "int"),
+            Self::I8 => write!(f, "i8"),
+            Self::I16 => write!(f, "i16"),
+            Self::I64 => write!(f, "i64"),
+            Self::U8 => write!(f, "u8"),
+            Self::U16 => write!(f, "u16"),
+            Self::U32 => write!(f, "u32"),
+            Self::U64 => write!(f, "u64"),
+            Self::F64 => write!(f, "f64"),
+            Self::Char => write!(f, "char"),
             Self::Void => write!(f, "void"),
             Self::Bool => write!(f, "bool"),
         }
     }
 }
 
+impl BasicType {
+    pub const NUMBERS: [BasicType; 10] = [
+        Self::I8,
+        Self::I16,
+        Self::Int,
+        Self::I64,
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::Float,
+        Self::F64,
+    ];
+
+    // the type a literal suffix like 10u8 or 2.5f64 names
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::NUMBERS
+            .iter()
+            .copied()
+            .find(|ty| ty.suffix() == suffix)
+    }
+
+    pub fn suffix(&self) -> String {
+        match self {
+            Self::Int => "i32".to_string(),
+            Self::Float => "f32".to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    // width in bits, 0 for anything that is not a number
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::I8 | Self::U8 => 8,
+            Self::I16 | Self::U16 => 16,
+            Self::Int | Self::U32 | Self::Float => 32,
+            Self::I64 | Self::U64 | Self::F64 => 64,
+            Self::Char | Self::Bool | Self::Void => 0,
+        }
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.bits() != 0 && !self.is_float()
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::Float | Self::F64)
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Self::I8 | Self::I16 | Self::Int | Self::I64)
+    }
+
+    // smallest and largest value an int literal of this type can hold
+    pub fn min(&self) -> i128 {
+        match self {
+            Self::I8 => i8::MIN as i128,
+            Self::I16 => i16::MIN as i128,
+            Self::Int => i32::MIN as i128,
+            Self::I64 => i64::MIN as i128,
+            _ => 0,
+        }
+    }
+
+    pub fn max(&self) -> i128 {
+        match self {
+            Self::I8 => i8::MAX as i128,
+            Self::I16 => i16::MAX as i128,
+            Self::Int => i32::MAX as i128,
+            Self::I64 => i64::MAX as i128,
+            Self::U8 => u8::MAX as i128,
+            Self::U16 => u16::MAX as i128,
+            Self::U32 => u32::MAX as i128,
+            _ => u64::MAX as i128,
+        }
+    }
+
+    // numbers only widen into a type that holds every value they can, ints also turn into floats
+    pub fn widens_into(&self, into: &Self) -> bool {
+        if *self == Self::Char {
+            // a char is its unsigned byte value
+            into.is_int() && (Self::U8.widens_into(into) || *into == Self::U8)
+        } else if self.is_int() && into.is_int() {
+            into.bits() > self.bits() && (into.is_signed() || !self.is_signed())
+        } else if self.is_int() && into.is_float() {
+            true
+        } else {
+            *self == Self::Float && *into == Self::F64
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionType {
     pub params: Vec<AtomType>,
@@ -52,14 +161,16 @@ impl Display for BlueprintType {
 #[derive(Debug, Clone)]
 pub struct Atom {
     pub name: String,
-    pub fields: HashMap<String, AtomType>,
+    pub fields: IndexMap<String, AtomType>, // in declaration order
     pub generics: IndexMap<String, AtomType>,
+    pub parts: Vec<String>, // fields that can only be set by new
+    pub bond: bool,         // bonds are stack allocated and copied by value
 }
 
+// atoms are compared by name so an atom can have fields of its own type
 impl PartialEq for Atom {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
-            && self.fields == other.fields
             && self.generics.keys().collect::<Vec<&String>>()
                 == other.generics.keys().collect::<Vec<&String>>()
     }
@@ -68,16 +179,25 @@ impl PartialEq for Atom {
 impl Atom {
     pub fn new(
         name: String,
-        fields: HashMap<String, AtomType>,
+        fields: IndexMap<String, AtomType>,
         generics: IndexMap<String, AtomType>,
     ) -> Atom {
         Atom {
             name,
             fields,
             generics,
+            parts: Vec::new(),
+            bond: false,
         }
     }
 
+    // atoms built into the compiler, every other atom is declared with the atom keyword
+    pub fn is_builtin(&self) -> bool {
+        [&*List, &*Str, &*Back, &*Const]
+            .iter()
+            .any(|builtin| builtin.name == self.name)
+    }
+
     // populates generics with given specs
     pub fn spec(&self, specs: &[AtomType]) -> Self {
         let mut this = self.clone();
@@ -93,7 +213,7 @@ macro_rules! complex {
     ($name:expr, { $($field_name:expr => $field_type:expr),* }, { $($generic_name:expr),* }) => {
         Atom::new(
             $name.to_owned(),
-            HashMap::from([$(($field_name.to_owned(), AtomType { kind: $field_type, details: None})),*]),
+            IndexMap::from([$(($field_name.to_owned(), AtomType { kind: $field_type, details: None})),*]),
             IndexMap::from([$(($generic_name.to_owned(), AtomType { kind: AtomKind::Unknown, details: None})),*]),
         )
     };
@@ -138,6 +258,7 @@ pub enum AtomKind {
     Dynamic, // may be scrapped, says that type is only known at runtime
     Unknown, // Unknown and no details means that expr type is unknown later on it should be replaced with Unknown(AtomType) where AtomType is an assumption and even later it is unwarped or converted to the Some type (may be replaced to be simpler)
     Any,     // mainly used for mangling and Symbol.expected, means that symbol can be of Any type
+    Error, // poison type given to nodes that failed to analyze, converts from and into anything so one error doesnt cascade
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -162,6 +283,7 @@ impl Display for AtomKind {
             AtomKind::Blueprint(b) => write!(f, "{}", b),
             AtomKind::Function(fun) => write!(f, "{}", fun),
             AtomKind::Unknown => write!(f, "Unknown"),
+            AtomKind::Error => write!(f, "error"),
         }
     }
 }
@@ -177,6 +299,10 @@ impl AtomType {
         self.details == Some(AtomDetails::Type)
     }
 
+    pub fn is_error(&self) -> bool {
+        self.kind == AtomKind::Error
+    }
+
     pub fn get(&self, name: &String) -> Option<&Self> {
         match &self.kind {
             AtomKind::Atom(a) => a.fields.get(name),
@@ -230,6 +356,8 @@ pub fn type_mangle(mut name: String, types: Vec<AtomType>) -> String {
                 .replace("(", "__")
                 .replace(")", "__")
                 .replace(",", "_")
+                .replace(" -> ", "_to_") // Fn(int) -> int
+                .replace(" ", "")
                 .as_str(),
         );
     }
@@ -246,8 +374,6 @@ pub fn mangle_types(mangle: String) -> Vec<String> {
 pub fn implicit_conversions(from: &AtomKind) -> Vec<AtomKind> {
     // AtomKind::Any conversions (anything convert to these)
     let mut results = vec![
-        AtomKind::Dynamic,
-        AtomKind::Atom(Str.clone()),
         // Const(T) (TODO! this is a bit of a hack, but it works for now (C backend const pointers))
         AtomKind::Atom(Const.spec(&[AtomType {
             kind: from.clone(),
@@ -255,11 +381,22 @@ pub fn implicit_conversions(from: &AtomKind) -> Vec<AtomKind> {
         }])),
     ];
 
+    // atoms and functions have no text to turn into, nor an Obj kind to be held by a Dynamic
+    match from {
+        AtomKind::Atom(atom) if atom != &*Str => (),
+        AtomKind::Function(_) | AtomKind::Blueprint(_) | AtomKind::Basic(BasicType::Void) => (),
+        _ => results.append(&mut vec![AtomKind::Dynamic, AtomKind::Atom(Str.clone())]),
+    }
+
     // reset of conversions for each specific type
     results.append(&mut match from {
         AtomKind::Atom(ref atom) if atom == &*Const => vec![atom.generics[0].kind.clone()],
 
-        AtomKind::Basic(BasicType::Int) => vec![AtomKind::Basic(BasicType::Float)],
+        AtomKind::Basic(ref basic) => BasicType::NUMBERS
+            .iter()
+            .filter(|into| basic.widens_into(into))
+            .map(|into| AtomKind::Basic(*into))
+            .collect(),
 
         AtomKind::Dynamic => vec![AtomKind::Any],
         _ => Vec::new(),
@@ -269,6 +406,10 @@ pub fn implicit_conversions(from: &AtomKind) -> Vec<AtomKind> {
 }
 
 pub fn can_implicitly_convert(from: &AtomKind, to: &AtomKind) -> bool {
+    if from == &AtomKind::Error || to == &AtomKind::Error {
+        return true;
+    }
+
     let conversions = implicit_conversions(from);
 
     conversions.contains(to) || conversions.contains(&AtomKind::Any)